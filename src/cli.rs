@@ -107,10 +107,24 @@ pub enum McpServer {
     Stdio(Stdio),
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfig {
+    #[serde(flatten)]
+    pub server: McpServer,
+
+    /// Prefix added to the server's tool names (default: the server name followed by '_')
+    pub prefix: Option<String>,
+
+    /// Time in seconds allowed to connect to the server and list its tools (default: 10)
+    pub connect_timeout: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Configuration {
     pub elasticsearch: elasticsearch::ElasticsearchMcpConfig,
+    /// Downstream MCP servers whose tools are exposed by this server
     #[serde(default)]
-    pub mcp_servers: HashMap<String, McpServer>,
+    pub mcp_servers: HashMap<String, McpServerConfig>,
 }
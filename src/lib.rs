@@ -23,7 +23,7 @@ mod k8s_port_forward;
 
 use crate::cli::{Cli, Command, Configuration, HttpCommand, StdioCommand};
 use crate::protocol::http::{HttpProtocol, HttpServerConfig};
-use crate::servers::elasticsearch;
+use crate::servers::{downstream, elasticsearch};
 use crate::utils::interpolator;
 use rmcp::transport::stdio;
 use rmcp::transport::streamable_http_server::session::never::NeverSessionManager;
@@ -142,6 +142,9 @@ pub async fn setup_services(config: &Option<PathBuf>, container_mode: bool) -> a
         Err(err) => return Err(err)?,
     };
 
-    let handler = elasticsearch::ElasticsearchMcp::new_with_config(config.elasticsearch, container_mode)?;
+    let downstream_routes = downstream::connect_all(config.mcp_servers).await;
+
+    let handler =
+        elasticsearch::ElasticsearchMcp::new_with_config(config.elasticsearch, container_mode, downstream_routes)?;
     Ok(handler)
 }
@@ -0,0 +1,158 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Downstream MCP servers declared in the `mcpServers` section of the configuration file. Their tools
+//! are exposed by this server with a name prefix, and calls are forwarded to them. Calls use the
+//! headers or environment of the server's configuration, whatever the credentials of the caller.
+
+use crate::cli::{Http, McpServer, McpServerConfig, Stdio};
+use futures::FutureExt;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use rmcp::ServiceExt;
+use rmcp::handler::server::tool::{ToolCallContext, ToolRoute};
+use rmcp::model::CallToolRequestParam;
+use rmcp::service::{RoleClient, RunningService, ServiceError};
+use rmcp::transport::sse_client::SseClientConfig;
+use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+use rmcp::transport::{SseClientTransport, StreamableHttpClientTransport, TokioChildProcess};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A connection to a downstream server. Dropping it closes the connection.
+type Connection = Arc<RunningService<RoleClient, ()>>;
+
+/// Default time allowed to connect to a downstream server and list its tools
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connect to all downstream servers concurrently and create tool routes that forward calls to them.
+///
+/// Servers that cannot be reached, or that don't answer within their connection timeout, are logged
+/// and skipped, so that a failing backend doesn't prevent the other tools from being available.
+pub async fn connect_all<S: Send + Sync + 'static>(servers: HashMap<String, McpServerConfig>) -> Vec<ToolRoute<S>> {
+    let connections = servers.into_iter().map(|(name, config)| async move {
+        let timeout = config
+            .connect_timeout
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+        let prefix = config.prefix.clone().unwrap_or_else(|| format!("{name}_"));
+
+        match tokio::time::timeout(timeout, connect_server(&config.server, &prefix)).await {
+            Ok(Ok(routes)) => {
+                tracing::info!("Downstream server '{name}': {} tools", routes.len());
+                routes
+            }
+            Ok(Err(e)) => {
+                tracing::error!("Downstream server '{name}': {e}");
+                Vec::new()
+            }
+            Err(_) => {
+                tracing::error!("Downstream server '{name}': no answer after {}s", timeout.as_secs());
+                Vec::new()
+            }
+        }
+    });
+
+    futures::future::join_all(connections)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+async fn connect_server<S: Send + Sync + 'static>(
+    server: &McpServer,
+    prefix: &str,
+) -> anyhow::Result<Vec<ToolRoute<S>>> {
+    let connection = connect(server)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to connect: {e}"))?;
+    tool_routes(connection, prefix)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to list tools: {e}"))
+}
+
+async fn connect(server: &McpServer) -> anyhow::Result<Connection> {
+    let connection = match server {
+        McpServer::Sse(Http { url, headers }) => {
+            let client = http_client(headers)?;
+            let config = SseClientConfig {
+                sse_endpoint: url.as_str().into(),
+                ..Default::default()
+            };
+            let transport = SseClientTransport::start_with_client(client, config).await?;
+            ().serve(transport).await?
+        }
+        McpServer::StreamableHttp(Http { url, headers }) => {
+            let client = http_client(headers)?;
+            let config = StreamableHttpClientTransportConfig::with_uri(url.as_str());
+            let transport = StreamableHttpClientTransport::with_client(client, config);
+            ().serve(transport).await?
+        }
+        McpServer::Stdio(Stdio { command, args, env }) => {
+            let mut cmd = tokio::process::Command::new(command);
+            cmd.args(args).envs(env);
+            ().serve(TokioChildProcess::new(cmd)?).await?
+        }
+    };
+
+    Ok(Arc::new(connection))
+}
+
+/// An http client that sends the configured headers with every request
+fn http_client(headers: &HashMap<String, String>) -> anyhow::Result<reqwest::Client> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+    }
+    Ok(reqwest::Client::builder().default_headers(header_map).build()?)
+}
+
+async fn tool_routes<S: Send + Sync + 'static>(
+    connection: Connection,
+    prefix: &str,
+) -> anyhow::Result<Vec<ToolRoute<S>>> {
+    let tools = connection.list_all_tools().await?;
+
+    let routes = tools
+        .into_iter()
+        .map(|mut tool| {
+            let connection = connection.clone();
+            let name = tool.name.clone();
+            tool.name = format!("{prefix}{name}").into();
+
+            ToolRoute::new_dyn(tool, move |ctx: ToolCallContext<'_, S>| {
+                let connection = connection.clone();
+                let request = CallToolRequestParam {
+                    name: name.clone(),
+                    arguments: ctx.arguments,
+                };
+                async move { connection.call_tool(request).await.map_err(forward_error) }.boxed()
+            })
+        })
+        .collect();
+
+    Ok(routes)
+}
+
+/// Forward MCP errors from the downstream server as is, other errors are internal errors.
+fn forward_error(e: ServiceError) -> rmcp::Error {
+    match e {
+        ServiceError::McpError(e) => e,
+        e => rmcp::Error::internal_error(e.to_string(), None),
+    }
+}
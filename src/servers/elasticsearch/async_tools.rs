@@ -0,0 +1,396 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tools for long-running queries: async ES|QL and async search.
+//!
+//! Queries are submitted and polled until they complete or a wait time has elapsed, sending
+//! progress notifications to the client. Queries that are still running return an id that
+//! can be used to get their results later.
+
+use crate::servers::elasticsearch::base_tools::{EsBaseTools, EsqlQueryRequest, EsqlQueryResponse, SearchResult};
+use crate::servers::elasticsearch::errors::ElasticsearchError;
+use crate::servers::elasticsearch::{EsqlResultFormat, handle_error, internal_error, read_json};
+use elasticsearch::Elasticsearch;
+use elasticsearch::async_search::{AsyncSearchDeleteParts, AsyncSearchGetParts, AsyncSearchSubmitParts};
+use elasticsearch::esql::{EsqlAsyncQueryDeleteParts, EsqlAsyncQueryGetParts};
+use rmcp::RoleServer;
+use rmcp::handler::server::tool::Parameters;
+use rmcp::model::{CallToolResult, Content, ProgressNotificationParam, ProgressToken};
+use rmcp::service::{Peer, RequestContext};
+use rmcp_macros::{tool, tool_router};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+
+/// How long results are kept by Elasticsearch
+const KEEP_ALIVE: &str = "1h";
+
+/// Wait time for each poll request, which also paces progress notifications
+const POLL_INTERVAL: &str = "1s";
+
+const DEFAULT_WAIT_SECONDS: u64 = 30;
+const MAX_WAIT_SECONDS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum AsyncQueryType {
+    /// An ES|QL query submitted with esql_async_submit
+    Esql,
+    /// A search submitted with async_search_submit
+    Search,
+}
+
+impl AsyncQueryType {
+    fn name(&self) -> &'static str {
+        match self {
+            AsyncQueryType::Esql => "esql",
+            AsyncQueryType::Search => "search",
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct EsqlAsyncSubmitParams {
+    /// Complete Elasticsearch ES|QL query
+    query: String,
+
+    /// Output format (optional). Tabular formats (csv, tsv, markdown) are more compact for large results.
+    format: Option<EsqlResultFormat>,
+
+    /// Values of named parameters used in the query as `?name` (optional)
+    params: Option<Map<String, Value>>,
+
+    /// Query DSL filter applied to documents before running the query, e.g. a time range (optional)
+    filter: Option<Map<String, Value>>,
+
+    /// How many seconds to wait for results before returning an id to get them later (optional, default 30)
+    wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct AsyncSearchSubmitParams {
+    /// Name of the Elasticsearch index to search
+    index: String,
+
+    /// Complete Elasticsearch query DSL object that can include query, size, from, sort, etc.
+    query_body: Map<String, Value>,
+
+    /// How many seconds to wait for results before returning an id to get them later (optional, default 30)
+    wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct GetAsyncResultParams {
+    /// Id returned when the query was submitted
+    id: String,
+
+    /// Type of the query
+    #[serde(rename = "type")]
+    type_: AsyncQueryType,
+
+    /// Output format for ES|QL results (optional)
+    format: Option<EsqlResultFormat>,
+
+    /// How many seconds to wait for results if the query is still running (optional, default 30)
+    wait_seconds: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct DeleteAsyncResultParams {
+    /// Id returned when the query was submitted
+    id: String,
+
+    /// Type of the query
+    #[serde(rename = "type")]
+    type_: AsyncQueryType,
+}
+
+#[derive(Serialize)]
+struct EsqlAsyncQueryRequest {
+    #[serde(flatten)]
+    request: EsqlQueryRequest,
+    wait_for_completion_timeout: String,
+    keep_alive: &'static str,
+}
+
+#[tool_router(router = async_tool_router, vis = "pub(super)")]
+impl EsBaseTools {
+    //---------------------------------------------------------------------------------------------
+    /// Tool: submit an async ES|QL query
+    #[tool(
+        description = "Perform a long-running Elasticsearch ES|QL query. Returns the results if the query completes within the wait time, or an id to get them later with get_async_result.",
+        annotations(title = "Elasticsearch async ES|QL query", read_only_hint = true)
+    )]
+    async fn esql_async_submit(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(EsqlAsyncSubmitParams {
+            query,
+            format,
+            params,
+            filter,
+            wait_seconds,
+        }): Parameters<EsqlAsyncSubmitParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let mut progress = Progress::new(&req_ctx, wait_seconds);
+        let es_client = self.es_client.get(req_ctx);
+
+        let request = EsqlAsyncQueryRequest {
+            request: EsqlQueryRequest {
+                query,
+                params: params.map(EsqlQueryRequest::named_params),
+                filter: filter.map(Value::Object),
+                ..Default::default()
+            },
+            wait_for_completion_timeout: POLL_INTERVAL.to_string(),
+            keep_alive: KEEP_ALIVE,
+        };
+
+        let response = es_client.esql().async_query().body(request).send().await;
+        let response: Value = read_json(response).await?;
+
+        let response = poll(&es_client, AsyncQueryType::Esql, response, &mut progress).await?;
+        async_result(AsyncQueryType::Esql, response, format)
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: submit an async search
+    #[tool(
+        description = "Perform a long-running Elasticsearch search with the provided query DSL. Returns the results if the search completes within the wait time, or an id to get them later with get_async_result.",
+        annotations(title = "Elasticsearch async search", read_only_hint = true)
+    )]
+    async fn async_search_submit(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(AsyncSearchSubmitParams {
+            index,
+            query_body,
+            wait_seconds,
+        }): Parameters<AsyncSearchSubmitParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let mut progress = Progress::new(&req_ctx, wait_seconds);
+        let es_client = self.es_client.get(req_ctx);
+
+        let response = es_client
+            .async_search()
+            .submit(AsyncSearchSubmitParts::Index(&[&index]))
+            .wait_for_completion_timeout(POLL_INTERVAL)
+            .keep_alive(KEEP_ALIVE)
+            .body(query_body)
+            .send()
+            .await;
+        let response: Value = read_json(response).await?;
+
+        let response = poll(&es_client, AsyncQueryType::Search, response, &mut progress).await?;
+        async_result(AsyncQueryType::Search, response, None)
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: get the results of an async query
+    #[tool(
+        description = "Get the results of a query submitted with esql_async_submit or async_search_submit, waiting for it to complete if it is still running.",
+        annotations(title = "Get async query results", read_only_hint = true)
+    )]
+    async fn get_async_result(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(GetAsyncResultParams {
+            id,
+            type_,
+            format,
+            wait_seconds,
+        }): Parameters<GetAsyncResultParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let mut progress = Progress::new(&req_ctx, wait_seconds);
+        let es_client = self.es_client.get(req_ctx);
+
+        let response = get(&es_client, type_, &id).await?;
+        let response = poll(&es_client, type_, response, &mut progress).await?;
+        async_result(type_, response, format)
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: delete an async query
+    #[tool(
+        description = "Cancel a running query submitted with esql_async_submit or async_search_submit, or delete its stored results.",
+        annotations(title = "Delete async query", destructive_hint = false, idempotent_hint = true)
+    )]
+    async fn delete_async_result(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(DeleteAsyncResultParams { id, type_ }): Parameters<DeleteAsyncResultParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+
+        let response = match type_ {
+            AsyncQueryType::Esql => {
+                es_client
+                    .esql()
+                    .async_query_delete(EsqlAsyncQueryDeleteParts::Id(&id))
+                    .send()
+                    .await
+            }
+            AsyncQueryType::Search => {
+                es_client
+                    .async_search()
+                    .delete(AsyncSearchDeleteParts::Id(&id))
+                    .send()
+                    .await
+            }
+        };
+        handle_error(response).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Deleted {} query {id}",
+            type_.name()
+        ))]))
+    }
+}
+
+/// Get the status and results of an async query, waiting at most `POLL_INTERVAL`.
+async fn get(es_client: &Elasticsearch, type_: AsyncQueryType, id: &str) -> Result<Value, rmcp::Error> {
+    let response = match type_ {
+        AsyncQueryType::Esql => {
+            es_client
+                .esql()
+                .async_query_get(EsqlAsyncQueryGetParts::Id(id))
+                .wait_for_completion_timeout(POLL_INTERVAL)
+                .send()
+                .await
+        }
+        AsyncQueryType::Search => {
+            es_client
+                .async_search()
+                .get(AsyncSearchGetParts::Id(id))
+                .wait_for_completion_timeout(POLL_INTERVAL)
+                .send()
+                .await
+        }
+    };
+    read_json(response).await
+}
+
+fn is_running(response: &Value) -> bool {
+    response.get("is_running").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Poll a running query until it completes or the wait time has elapsed.
+async fn poll(
+    es_client: &Elasticsearch,
+    type_: AsyncQueryType,
+    mut response: Value,
+    progress: &mut Progress,
+) -> Result<Value, rmcp::Error> {
+    while is_running(&response) && !progress.expired() {
+        let Some(id) = response.get("id").and_then(Value::as_str) else {
+            break;
+        };
+        let id = id.to_string();
+        progress
+            .notify(format!("Waiting for {} query {id}", type_.name()))
+            .await;
+        response = get(es_client, type_, &id).await?;
+    }
+    Ok(response)
+}
+
+/// Tool result for an async query response: results if it has completed, or its id otherwise.
+fn async_result(
+    type_: AsyncQueryType,
+    mut response: Value,
+    format: Option<EsqlResultFormat>,
+) -> Result<CallToolResult, rmcp::Error> {
+    let id = response.get("id").and_then(Value::as_str).map(str::to_string);
+
+    if is_running(&response) {
+        let id = id.unwrap_or_default();
+        return Ok(CallToolResult::success(vec![Content::text(format!(
+            "The query is still running. Call get_async_result with id '{id}' and type '{}' to get its results, \
+             or delete_async_result to cancel it.",
+            type_.name()
+        ))]));
+    }
+
+    let mut results = vec![Content::text("Results")];
+    match type_ {
+        AsyncQueryType::Esql => {
+            let response: EsqlQueryResponse = serde_json::from_value(response).map_err(internal_error)?;
+            results.extend(response.partial_warning());
+            results.extend(format.unwrap_or_default().format(response)?);
+        }
+        AsyncQueryType::Search => {
+            // A failed search has an error and no response, with its status in completion_status
+            if let Some(error) = response.get("error") {
+                let status = response
+                    .get("completion_status")
+                    .or_else(|| error.get("status"))
+                    .and_then(Value::as_u64)
+                    .unwrap_or(500) as u16;
+                return Ok(ElasticsearchError::parse(status, &response.to_string()).into_tool_result());
+            }
+            let response: SearchResult = serde_json::from_value(response["response"].take()).map_err(internal_error)?;
+            results.extend(response.into_contents(false)?);
+        }
+    }
+
+    if let Some(id) = id {
+        results.push(Content::text(format!(
+            "Results are stored with id '{id}' for {KEEP_ALIVE}, call delete_async_result to delete them."
+        )));
+    }
+    Ok(CallToolResult::success(results))
+}
+
+/// Progress notifications, sent if the client provided a progress token.
+struct Progress {
+    peer: Peer<RoleServer>,
+    token: Option<ProgressToken>,
+    start: Instant,
+    wait: Duration,
+}
+
+impl Progress {
+    fn new(context: &RequestContext<RoleServer>, wait_seconds: Option<u64>) -> Self {
+        let wait_seconds = wait_seconds.unwrap_or(DEFAULT_WAIT_SECONDS).min(MAX_WAIT_SECONDS);
+        Progress {
+            peer: context.peer.clone(),
+            token: context.meta.get_progress_token(),
+            start: Instant::now(),
+            wait: Duration::from_secs(wait_seconds),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.start.elapsed() >= self.wait
+    }
+
+    /// Notify the elapsed time, out of the wait time.
+    async fn notify(&self, message: String) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        let param = ProgressNotificationParam {
+            progress_token: token.clone(),
+            progress: self.start.elapsed().as_secs() as u32,
+            total: Some(self.wait.as_secs() as u32),
+            message: Some(message),
+        };
+        if let Err(e) = self.peer.notify_progress(param).await {
+            tracing::debug!("Failed to send progress notification: {e}");
+        }
+    }
+}
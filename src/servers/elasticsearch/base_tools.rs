@@ -15,34 +15,73 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::servers::elasticsearch::{EsClientProvider, read_json};
+use crate::servers::elasticsearch::completion::Completions;
+use crate::servers::elasticsearch::errors::ElasticsearchError;
+use crate::servers::elasticsearch::index_tools::DataStream;
+use crate::servers::elasticsearch::mappings::{FieldCatalog, MappingResponse};
+use crate::servers::elasticsearch::prompts::Prompts;
+use crate::servers::elasticsearch::{
+    EsClientProvider, EsqlResultFormat, PromptConfig, Tools, custom_tools, index_tools, pagination, read_json,
+    resources, suggestions,
+};
 use elasticsearch::cat::{CatIndicesParts, CatShardsParts};
-use elasticsearch::indices::IndicesGetMappingParts;
-use elasticsearch::{Elasticsearch, SearchParts};
+use elasticsearch::indices::{IndicesGetMappingParts, IndicesValidateQueryParts};
+use elasticsearch::{CountParts, Elasticsearch, SearchParts};
 use indexmap::IndexMap;
-use rmcp::handler::server::tool::{Parameters, ToolRouter};
+use rmcp::handler::server::tool::{Parameters, ToolCallContext, ToolRoute, ToolRouter};
 use rmcp::model::{
-    CallToolResult, Content, Implementation, JsonObject, ProtocolVersion, ServerCapabilities, ServerInfo,
+    CallToolRequestParam, CallToolResult, CompleteRequestParam, CompleteResult, CompletionInfo, Content,
+    GetPromptRequestParam, GetPromptResult, Implementation, JsonObject, ListPromptsResult, ListResourceTemplatesResult,
+    ListResourcesResult, ListToolsResult, PaginatedRequestParam, PromptsCapability, ProtocolVersion,
+    ReadResourceRequestParam, ReadResourceResult, Reference, ResourcesCapability, ServerCapabilities, ServerInfo,
 };
 use rmcp::service::RequestContext;
 use rmcp::{RoleServer, ServerHandler};
-use rmcp_macros::{tool, tool_handler, tool_router};
+use rmcp_macros::{tool, tool_router};
 use serde::{Deserialize, Serialize};
 use serde_aux::prelude::*;
 use serde_json::{Map, Value, json};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct EsBaseTools {
-    es_client: EsClientProvider,
+    pub(super) es_client: EsClientProvider,
     tool_router: ToolRouter<EsBaseTools>,
+    prompts: Arc<Prompts>,
+    completions: Arc<Completions>,
 }
 
 impl EsBaseTools {
-    pub fn new(es_client: Elasticsearch) -> Self {
+    pub fn new(
+        es_client: Elasticsearch,
+        tools: Tools,
+        prompts: IndexMap<String, PromptConfig>,
+        extra_routes: Vec<ToolRoute<EsBaseTools>>,
+    ) -> Self {
+        let mut tool_router = Self::tool_router()
+            + Self::async_tool_router()
+            + Self::cluster_tool_router()
+            + Self::index_tool_router()
+            + Self::field_tool_router()
+            + Self::document_tool_router();
+        for route in custom_tools::routes(tools.custom).into_iter().chain(extra_routes) {
+            // Adding a route replaces any existing one with the same name
+            if tool_router.has_route(route.name()) {
+                tracing::warn!("Tool '{}' is already defined, ignoring the duplicate", route.name());
+                continue;
+            }
+            tool_router.add_route(route);
+        }
+        if let Some(incl_excl) = &tools.incl_excl {
+            incl_excl.filter_router(&mut tool_router);
+        }
+
         Self {
             es_client: EsClientProvider::new(es_client),
-            tool_router: Self::tool_router(),
+            tool_router,
+            prompts: Arc::new(Prompts::new(prompts)),
+            completions: Arc::new(Completions::default()),
         }
     }
 }
@@ -51,30 +90,82 @@ impl EsBaseTools {
 struct ListIndicesParams {
     /// Index pattern of Elasticsearch indices to list
     pub index_pattern: String,
+
+    /// Set to true to also get each index's health, number of primary shards and replicas, and store size (optional)
+    pub details: Option<bool>,
+
+    /// Set to true to group the backing indices of data streams under their data stream (optional)
+    pub group_data_streams: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 struct GetMappingsParams {
-    /// Name of the Elasticsearch index to get mappings for
+    /// Name of the Elasticsearch index, alias or index pattern to get mappings for
     index: String,
+
+    /// Output format (optional). Defaults to flat, which also lists multi-fields like `message.keyword`.
+    format: Option<MappingFormat>,
+}
+
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum MappingFormat {
+    /// Fields by dotted path, merged across indices
+    #[default]
+    Flat,
+    /// Mappings as returned by Elasticsearch, for each index
+    Raw,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 struct SearchParams {
-    /// Name of the Elasticsearch index to search
-    index: String,
+    /// Name of the Elasticsearch index to search. Required, except with a cursor.
+    index: Option<String>,
 
     /// Name of the fields that need to be returned (optional)
     fields: Option<Vec<String>>,
 
     /// Complete Elasticsearch query DSL object that can include query, size, from, sort, etc.
+    /// Not needed with a cursor.
+    #[serde(default)]
     query_body: Map<String, Value>, // note: just Value doesn't work, as Claude would send a string
+
+    /// Set to true to page through results: the result will include a cursor to get the next page (optional)
+    paginate: Option<bool>,
+
+    /// Cursor returned by a previous search, to get the next page of results (optional)
+    cursor: Option<String>,
+
+    /// Set to true to include each document's metadata (_id, _index, _score) and, if requested in the
+    /// query, its highlights, fields, inner hits and sort values (optional)
+    include_metadata: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct QueryParams {
+    /// Name of the Elasticsearch index, alias or index pattern
+    index: String,
+
+    /// Query DSL object, as for the search tool. Only its `query` property is used (optional, default
+    /// all documents).
+    #[serde(default)]
+    query_body: Map<String, Value>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 struct EsqlQueryParams {
     /// Complete Elasticsearch ES|QL query
     query: String,
+
+    /// Output format (optional). Tabular formats (csv, tsv, markdown) are more compact for large results.
+    format: Option<EsqlResultFormat>,
+
+    /// Values of named parameters used in the query as `?name` (optional). Use parameters rather than
+    /// inserting values in the query text.
+    params: Option<Map<String, Value>>,
+
+    /// Query DSL filter applied to documents before running the query, e.g. a time range (optional)
+    filter: Option<Map<String, Value>>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -94,19 +185,33 @@ impl EsBaseTools {
     async fn list_indices(
         &self,
         req_ctx: RequestContext<RoleServer>,
-        Parameters(ListIndicesParams { index_pattern }): Parameters<ListIndicesParams>,
+        Parameters(ListIndicesParams {
+            index_pattern,
+            details,
+            group_data_streams,
+        }): Parameters<ListIndicesParams>,
     ) -> Result<CallToolResult, rmcp::Error> {
         let es_client = self.es_client.get(req_ctx);
+        let columns: &[&str] = if details.unwrap_or(false) {
+            &["index", "status", "docs.count", "health", "pri", "rep", "store.size"]
+        } else {
+            &["index", "status", "docs.count"]
+        };
         let response = es_client
             .cat()
             .indices(CatIndicesParts::Index(&[&index_pattern]))
-            .h(&["index", "status", "docs.count"])
+            .h(columns)
             .format("json")
             .send()
             .await;
 
         let response: Vec<CatIndexResponse> = read_json(response).await?;
 
+        if group_data_streams.unwrap_or(false) {
+            let streams = index_tools::data_streams(&es_client, None).await?;
+            return Ok(CallToolResult::success(group_by_data_stream(response, streams)?));
+        }
+
         Ok(CallToolResult::success(vec![
             Content::text(format!("Found {} indices:", response.len())),
             Content::json(response)?,
@@ -116,14 +221,15 @@ impl EsBaseTools {
     //---------------------------------------------------------------------------------------------
     /// Tool: get mappings for an index
     #[tool(
-        description = "Get field mappings for a specific Elasticsearch index",
+        description = "Get field mappings for an Elasticsearch index, alias or index pattern. By default, fields are listed by dotted path (including multi-fields like `message.keyword` and runtime fields) and merged across matching indices.",
         annotations(title = "Get ES index mappings", read_only_hint = true)
     )]
     async fn get_mappings(
         &self,
         req_ctx: RequestContext<RoleServer>,
-        Parameters(GetMappingsParams { index }): Parameters<GetMappingsParams>,
+        Parameters(GetMappingsParams { index, format }): Parameters<GetMappingsParams>,
     ) -> Result<CallToolResult, rmcp::Error> {
+        let credentials = self.es_client.credentials_key(&req_ctx);
         let es_client = self.es_client.get(req_ctx);
         let response = es_client
             .indices()
@@ -132,13 +238,52 @@ impl EsBaseTools {
             .await;
 
         let response: MappingResponse = read_json(response).await?;
+        if response.is_empty() {
+            // Wildcards are removed, as suggestions compare the pattern to existing names
+            let mut message = format!("No index matches '{index}'.");
+            let suggestions =
+                suggestions::similar_indices(&self.completions, &es_client, credentials, index.trim_matches('*')).await;
+            if !suggestions.is_empty() {
+                message.push_str(&format!(" Did you mean: {}?", suggestions.join(", ")));
+            }
+            message.push_str(" Use list_indices to find existing indices.");
+            return Ok(CallToolResult::error(vec![Content::text(message)]));
+        }
 
-        // use the first mapping (we can have many if the name is a wildcard)
-        let mapping = response.values().next().unwrap();
+        if let MappingFormat::Raw = format.unwrap_or_default() {
+            let mut indices = response.keys().collect::<Vec<_>>();
+            indices.sort();
+            return Ok(CallToolResult::success(match indices.as_slice() {
+                [single] => vec![
+                    Content::text(format!("Mappings for index {single}:")),
+                    Content::json(&response[*single].mappings)?,
+                ],
+                _ => vec![
+                    Content::text(format!("Mappings for {} indices matching {index}:", indices.len())),
+                    Content::json(&response)?,
+                ],
+            }));
+        }
+
+        let catalog = FieldCatalog::new(&response);
+        let mut summary = match catalog.indices.as_slice() {
+            [single] => format!("Fields of index {single}:"),
+            indices => format!(
+                "Fields of {} indices matching {index}, merged by path. Fields that don't exist in all \
+                 indices list the indices that have them.",
+                indices.len()
+            ),
+        };
+        let conflicts = catalog.conflicts();
+        if conflicts > 0 {
+            summary.push_str(&format!(
+                " {conflicts} fields have a type that differs between indices (type 'conflict')."
+            ));
+        }
 
         Ok(CallToolResult::success(vec![
-            Content::text(format!("Mappings for index {index}:")),
-            Content::json(mapping)?,
+            Content::text(summary),
+            Content::json(catalog)?,
         ]))
     }
 
@@ -148,7 +293,7 @@ impl EsBaseTools {
     /// The additional 'fields' parameter helps some LLMs that don't know about the `_source`
     /// request property to narrow down the data returned and reduce their context size
     #[tool(
-        description = "Perform an Elasticsearch search with the provided query DSL.",
+        description = "Perform an Elasticsearch search with the provided query DSL. Set `paginate` to get a cursor to fetch the next pages of results, even beyond 10,000 hits.",
         annotations(title = "Elasticsearch search DSL query", read_only_hint = true)
     )]
     async fn search(
@@ -158,9 +303,23 @@ impl EsBaseTools {
             index,
             fields,
             query_body,
+            paginate,
+            cursor,
+            include_metadata,
         }): Parameters<SearchParams>,
     ) -> Result<CallToolResult, rmcp::Error> {
         let es_client = self.es_client.get(req_ctx);
+        let include_metadata = include_metadata.unwrap_or(false);
+
+        if let Some(cursor) = cursor {
+            let (response, cursor) = pagination::next_page(&es_client, &cursor).await?;
+            let contents = search_contents(response, cursor, include_metadata)?;
+            return Ok(CallToolResult::success(contents));
+        }
+
+        let Some(index) = index else {
+            return Err(rmcp::Error::invalid_params("missing index", None));
+        };
 
         let mut query_body = query_body;
 
@@ -175,6 +334,12 @@ impl EsBaseTools {
             }
         }
 
+        if paginate.unwrap_or(false) {
+            let (response, cursor) = pagination::first_page(&es_client, &index, query_body).await?;
+            let contents = search_contents(response, cursor, include_metadata)?;
+            return Ok(CallToolResult::success(contents));
+        }
+
         let response = es_client
             .search(SearchParts::Index(&[&index]))
             .body(query_body)
@@ -183,38 +348,59 @@ impl EsBaseTools {
 
         let response: SearchResult = read_json(response).await?;
 
-        let mut results: Vec<Content> = Vec::new();
+        Ok(CallToolResult::success(response.into_contents(include_metadata)?))
+    }
 
-        // Send result stats only if it's not pure aggregation results
-        if response.aggregations.is_empty() || !response.hits.hits.is_empty() {
-            let total = response
-                .hits
-                .total
-                .map(|t| t.value.to_string())
-                .unwrap_or("unknown".to_string());
+    //---------------------------------------------------------------------------------------------
+    /// Tool: count documents matching a query
+    #[tool(
+        description = "Count the documents matching a query DSL, to check a query before running a search.",
+        annotations(title = "Count ES documents", read_only_hint = true)
+    )]
+    async fn count(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(QueryParams { index, query_body }): Parameters<QueryParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let response = es_client
+            .count(CountParts::Index(&[&index]))
+            .body(query_only(query_body))
+            .send()
+            .await;
 
-            results.push(Content::text(format!(
-                "Total results: {}, showing {}.",
-                total,
-                response.hits.hits.len()
-            )));
-        }
+        let response: CountResponse = read_json(response).await?;
 
-        // Original prototype sent a separate content for each document, it seems to confuse some LLMs
-        // for hit in &response.hits.hits {
-        //     results.push(Content::json(&hit.source)?);
-        // }
-        if !response.hits.hits.is_empty() {
-            let sources = response.hits.hits.iter().map(|hit| &hit.source).collect::<Vec<_>>();
-            results.push(Content::json(&sources)?);
-        }
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{} documents match the query in {index}.",
+            response.count
+        ))]))
+    }
 
-        if !response.aggregations.is_empty() {
-            results.push(Content::text("Aggregations results:"));
-            results.push(Content::json(&response.aggregations)?);
-        }
+    //---------------------------------------------------------------------------------------------
+    /// Tool: validate a query
+    #[tool(
+        description = "Check whether a query DSL is valid without running it. For valid queries, returns the Lucene query it is rewritten to, which shows how fields and text are interpreted.",
+        annotations(title = "Validate ES query", read_only_hint = true)
+    )]
+    async fn validate_query(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(QueryParams { index, query_body }): Parameters<QueryParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let response = es_client
+            .indices()
+            .validate_query(IndicesValidateQueryParts::Index(&[&index]))
+            .explain(true)
+            .rewrite(true)
+            .body(query_only(query_body))
+            .send()
+            .await;
 
-        Ok(CallToolResult::success(results))
+        let response: ValidateQueryResponse = read_json(response).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(response.summary())]))
     }
 
     //---------------------------------------------------------------------------------------------
@@ -226,29 +412,30 @@ impl EsBaseTools {
     async fn esql(
         &self,
         req_ctx: RequestContext<RoleServer>,
-        Parameters(EsqlQueryParams { query }): Parameters<EsqlQueryParams>,
+        Parameters(EsqlQueryParams {
+            query,
+            format,
+            params,
+            filter,
+        }): Parameters<EsqlQueryParams>,
     ) -> Result<CallToolResult, rmcp::Error> {
         let es_client = self.es_client.get(req_ctx);
 
-        let request = EsqlQueryRequest { query };
+        let request = EsqlQueryRequest {
+            query,
+            params: params.map(EsqlQueryRequest::named_params),
+            filter: filter.map(Value::Object),
+            ..Default::default()
+        };
 
         let response = es_client.esql().query().body(request).send().await;
         let response: EsqlQueryResponse = read_json(response).await?;
 
-        // Transform response into an array of objects
-        let mut objects: Vec<Value> = Vec::new();
-        for row in response.values.into_iter() {
-            let mut obj = Map::new();
-            for (i, value) in row.into_iter().enumerate() {
-                obj.insert(response.columns[i].name.clone(), value);
-            }
-            objects.push(Value::Object(obj));
-        }
+        let mut results = vec![Content::text("Results")];
+        results.extend(response.partial_warning());
+        results.extend(format.unwrap_or_default().format(response)?);
 
-        Ok(CallToolResult::success(vec![
-            Content::text("Results"),
-            Content::json(objects)?,
-        ]))
+        Ok(CallToolResult::success(results))
     }
 
     //---------------------------------------------------------------------------------------------
@@ -282,23 +469,144 @@ impl EsBaseTools {
 
         let response: Vec<CatShardsResponse> = read_json(response).await?;
 
-        Ok(CallToolResult::success(vec![
+        let mut results = vec![
             Content::text(format!("Found {} shards:", response.len())),
-            Content::json(response)?,
-        ]))
+            Content::json(&response)?,
+        ];
+        let unassigned = response.iter().filter(|s| s.state == "UNASSIGNED").count();
+        if unassigned > 0 {
+            results.push(Content::text(format!(
+                "{unassigned} shards are unassigned. Use explain_allocation with their index, shard number and \
+                 primary (prirep 'p') to know why."
+            )));
+        }
+
+        Ok(CallToolResult::success(results))
     }
 }
 
-#[tool_handler]
 impl ServerHandler for EsBaseTools {
     fn get_info(&self) -> ServerInfo {
+        let mut capabilities = ServerCapabilities::builder().enable_tools().build();
+        capabilities.resources = Some(ResourcesCapability::default());
+        capabilities.completions = Some(JsonObject::new());
+        if !self.prompts.is_empty() {
+            capabilities.prompts = Some(PromptsCapability::default());
+        }
+
         ServerInfo {
             protocol_version: ProtocolVersion::V_2025_03_26,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities,
             server_info: Implementation::from_build_env(),
             instructions: Some("Provides access to Elasticsearch".to_string()),
         }
     }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, rmcp::Error> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    /// Errors returned by Elasticsearch are sent as error results, as the model may be able
+    /// to fix its request. Missing indices come with suggestions of similar existing names.
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let tcc = ToolCallContext::new(self, request, context.clone());
+        let error = match self.tool_router.call(tcc).await {
+            Ok(result) => return Ok(result),
+            Err(error) => error,
+        };
+
+        let Some(mut es_error) = ElasticsearchError::from_rmcp_error(&error) else {
+            return Err(error);
+        };
+        if let Some(index) = es_error.missing_index() {
+            let credentials = self.es_client.credentials_key(&context);
+            let es_client = self.es_client.get(context);
+            es_error.suggestions =
+                suggestions::similar_indices(&self.completions, &es_client, credentials, index).await;
+        }
+
+        Ok(es_error.into_tool_result())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::Error> {
+        Ok(ListPromptsResult::with_all_items(self.prompts.list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, rmcp::Error> {
+        self.prompts.get(&request.name, request.arguments)
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::Error> {
+        let es_client = self.es_client.get(context);
+        Ok(ListResourcesResult::with_all_items(resources::list(&es_client).await?))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, rmcp::Error> {
+        Ok(ListResourceTemplatesResult::with_all_items(resources::templates()))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let es_client = self.es_client.get(context);
+        resources::read(&es_client, &request.uri).await
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, rmcp::Error> {
+        let completion = match &request.r#ref {
+            Reference::Prompt(prompt) => self.prompts.completion(&prompt.name, &request.argument.name),
+            Reference::Resource(resource) => resources::completion(&resource.uri, &request.argument.name),
+        };
+
+        let Some(completion) = completion else {
+            return Ok(CompleteResult {
+                completion: CompletionInfo {
+                    values: Vec::new(),
+                    total: None,
+                    has_more: None,
+                },
+            });
+        };
+
+        let credentials = self.es_client.credentials_key(&context);
+        let es_client = self.es_client.get(context);
+        let completion = self
+            .completions
+            .complete(&es_client, credentials, &completion, &request.argument.value)
+            .await?;
+
+        Ok(CompleteResult { completion })
+    }
 }
 
 //-------------------------------------------------------------------------------------------------
@@ -308,11 +616,119 @@ impl ServerHandler for EsBaseTools {
 
 #[derive(Serialize, Deserialize)]
 pub struct SearchResult {
+    /// Point in time id, for searches on a PIT
+    pub pit_id: Option<String>,
     pub hits: Hits,
     #[serde(default)]
     pub aggregations: IndexMap<String, Value>,
 }
 
+impl SearchResult {
+    /// Tool result content: result stats, documents and aggregations. Documents are output with
+    /// their metadata (id, index, score, highlights, etc.) if `include_metadata` is true, otherwise
+    /// only their `_source` is output.
+    pub fn into_contents(self, include_metadata: bool) -> Result<Vec<Content>, rmcp::Error> {
+        let mut results: Vec<Content> = Vec::new();
+
+        // Send result stats only if it's not pure aggregation results
+        if self.aggregations.is_empty() || !self.hits.hits.is_empty() {
+            let total = self
+                .hits
+                .total
+                .map(|t| t.value.to_string())
+                .unwrap_or("unknown".to_string());
+
+            results.push(Content::text(format!(
+                "Total results: {}, showing {}.",
+                total,
+                self.hits.hits.len()
+            )));
+        }
+
+        // Original prototype sent a separate content for each document, it seems to confuse some LLMs
+        // for hit in &self.hits.hits {
+        //     results.push(Content::json(&hit.source)?);
+        // }
+        if include_metadata && !self.hits.hits.is_empty() {
+            results.push(Content::json(&self.hits.hits)?);
+        } else if !self.hits.hits.is_empty() {
+            let sources = self.hits.hits.iter().map(|hit| &hit.source).collect::<Vec<_>>();
+            results.push(Content::json(&sources)?);
+        }
+
+        if !self.aggregations.is_empty() {
+            results.push(Content::text("Aggregations results:"));
+            results.push(Content::json(&self.aggregations)?);
+        }
+
+        Ok(results)
+    }
+}
+
+/// Indices, with the backing indices of data streams grouped under their data stream
+fn group_by_data_stream(indices: Vec<CatIndexResponse>, streams: Vec<DataStream>) -> Result<Vec<Content>, rmcp::Error> {
+    let stream_of = streams
+        .iter()
+        .flat_map(|s| s.indices.iter().map(|i| (i.index_name.as_str(), s.name.as_str())))
+        .collect::<HashMap<_, _>>();
+
+    let mut grouped: IndexMap<&str, DataStreamIndices> = IndexMap::new();
+    let mut other = Vec::new();
+    for index in indices {
+        match stream_of.get(index.index.as_str()) {
+            Some(stream) => {
+                let entry = grouped.entry(stream).or_insert_with(|| DataStreamIndices {
+                    data_stream: stream.to_string(),
+                    doc_count: 0,
+                    backing_indices: Vec::new(),
+                });
+                entry.doc_count += index.doc_count.unwrap_or_default();
+                entry.backing_indices.push(index.index);
+            }
+            None => other.push(index),
+        }
+    }
+    grouped.sort_keys();
+
+    let mut results = vec![Content::text(format!(
+        "Found {} indices and {} data streams. Query data streams by their name rather than their backing indices.",
+        other.len(),
+        grouped.len()
+    ))];
+    if !other.is_empty() {
+        results.push(Content::json(&other)?);
+    }
+    if !grouped.is_empty() {
+        results.push(Content::text("Data streams:"));
+        results.push(Content::json(grouped.into_values().collect::<Vec<_>>())?);
+    }
+    Ok(results)
+}
+
+/// Keep only the `query` of a search request body, for APIs that don't accept other properties
+fn query_only(mut query_body: Map<String, Value>) -> Value {
+    match query_body.remove("query") {
+        Some(query) => json!({ "query": query }),
+        None => json!({}),
+    }
+}
+
+/// Search results followed by the cursor to get the next page, if any
+fn search_contents(
+    response: SearchResult,
+    cursor: Option<String>,
+    include_metadata: bool,
+) -> Result<Vec<Content>, rmcp::Error> {
+    let mut results = response.into_contents(include_metadata)?;
+    match cursor {
+        Some(cursor) => results.push(Content::text(format!(
+            "More results are available. To get the next page, search with cursor: {cursor}"
+        ))),
+        None => results.push(Content::text("No more results.")),
+    }
+    Ok(results)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Hits {
     pub total: Option<TotalHits>,
@@ -326,18 +742,125 @@ pub struct TotalHits {
 
 #[derive(Serialize, Deserialize)]
 pub struct Hit {
-    #[serde(rename = "_source")]
+    #[serde(rename = "_index", skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "_score", skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Missing if `_source` is disabled in the request
+    #[serde(rename = "_source", default, skip_serializing_if = "Value::is_null")]
     pub source: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<Value>,
+    /// Values requested with `fields` or `docvalue_fields`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner_hits: Option<Value>,
+    /// Sort values, also used to get the next page with `search_after`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<Value>>,
+}
+
+//----- Count and validate responses
+
+#[derive(Deserialize)]
+pub struct CountResponse {
+    pub count: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ValidateQueryResponse {
+    pub valid: bool,
+    /// Top-level error, if the request couldn't be parsed
+    pub error: Option<String>,
+    #[serde(default)]
+    pub explanations: Vec<QueryExplanation>,
+}
+
+#[derive(Deserialize)]
+pub struct QueryExplanation {
+    pub index: Option<String>,
+    pub explanation: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ValidateQueryResponse {
+    /// Validity of the query, with the Lucene queries or errors, grouped by index when they differ.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![if self.valid {
+            "The query is valid.".to_string()
+        } else {
+            "The query is not valid.".to_string()
+        }];
+        if let Some(error) = &self.error {
+            lines.push(error.clone());
+        }
+
+        // Explanation -> indices
+        let mut explanations: IndexMap<&str, Vec<&str>> = IndexMap::new();
+        for expl in &self.explanations {
+            if let Some(text) = expl.error.as_deref().or(expl.explanation.as_deref()) {
+                explanations
+                    .entry(text)
+                    .or_default()
+                    .push(expl.index.as_deref().unwrap_or("unknown"));
+            }
+        }
+        let label = if self.valid { "Lucene query" } else { "Error" };
+        match explanations.len() {
+            0 => {}
+            1 => lines.push(format!("{label}: {}", explanations.keys().next().unwrap())),
+            _ => {
+                for (text, indices) in explanations {
+                    lines.push(format!("{label} for {}: {text}", indices.join(", ")));
+                }
+            }
+        }
+        lines.join("\n")
+    }
 }
 
 //----- Cat responses
 
+/// Backing indices of a data stream, in `list_indices`
+#[derive(Serialize)]
+pub struct DataStreamIndices {
+    pub data_stream: String,
+    #[serde(rename = "docs.count")]
+    pub doc_count: u64,
+    pub backing_indices: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CatIndexResponse {
     pub index: String,
     pub status: String,
-    #[serde(rename = "docs.count", deserialize_with = "deserialize_number_from_string")]
-    pub doc_count: u64,
+    /// Missing for closed indices
+    #[serde(
+        rename = "docs.count",
+        default,
+        deserialize_with = "deserialize_option_number_from_string"
+    )]
+    pub doc_count: Option<u64>,
+    // Optional columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_option_number_from_string"
+    )]
+    pub pri: Option<u64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_option_number_from_string"
+    )]
+    pub rep: Option<u64>,
+    #[serde(rename = "store.size", skip_serializing_if = "Option::is_none")]
+    pub store_size: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -353,35 +876,33 @@ pub struct CatShardsResponse {
     pub node: Option<String>,
 }
 
-//----- Index mappings
-
-pub type MappingResponse = HashMap<String, Mappings>;
-
-#[derive(Serialize, Deserialize)]
-pub struct Mappings {
-    pub mappings: Mapping,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct Mapping {
-    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
-    pub meta: Option<JsonObject>,
-    properties: HashMap<String, MappingProperty>,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct MappingProperty {
-    #[serde(rename = "type")]
-    pub type_: String,
-    #[serde(flatten)]
-    pub settings: HashMap<String, serde_json::Value>,
-}
-
 //----- ES|QL
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 pub struct EsqlQueryRequest {
     pub query: String,
+    /// Named parameters, as a list of single-property objects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Vec<Value>>,
+    /// Query DSL filter applied before running the query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    /// Locale used to format dates and numbers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Return values by column rather than by row. `EsqlQueryResponse` expects rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columnar: Option<bool>,
+}
+
+impl EsqlQueryRequest {
+    /// Convert named parameters to the list of single-property objects expected by ES|QL
+    pub fn named_params(params: impl IntoIterator<Item = (impl Into<String>, Value)>) -> Vec<Value> {
+        params
+            .into_iter()
+            .map(|(name, value)| Value::Object(Map::from_iter([(name.into(), value)])))
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -397,3 +918,198 @@ pub struct EsqlQueryResponse {
     pub columns: Vec<Column>,
     pub values: Vec<Vec<Value>>,
 }
+
+impl EsqlQueryResponse {
+    /// A warning if results are incomplete (e.g. some shards or remote clusters failed)
+    pub fn partial_warning(&self) -> Option<Content> {
+        self.is_partial
+            .unwrap_or(false)
+            .then(|| Content::text("Warning: results are partial, some shards or clusters could not be queried."))
+    }
+
+    /// Transform the response rows into an array of objects
+    pub fn into_objects(self) -> Vec<Value> {
+        let mut objects: Vec<Value> = Vec::new();
+        for row in self.values.into_iter() {
+            let mut obj = Map::new();
+            for (i, value) in row.into_iter().enumerate() {
+                obj.insert(self.columns[i].name.clone(), value);
+            }
+            objects.push(Value::Object(obj));
+        }
+        objects
+    }
+
+    /// Column names and types, in column order
+    pub fn column_types(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| format!("{} ({})", c.name, c.type_))
+            .collect::<Vec<_>>();
+        format!("Columns: {}", columns.join(", "))
+    }
+
+    /// Render as delimiter-separated values with a header row. Values are quoted as in CSV (RFC 4180)
+    /// for a comma separator, and escaped as in TSV otherwise.
+    pub fn to_delimited(&self, separator: char) -> String {
+        let escape = |s: &str| {
+            if separator == ',' {
+                if s.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", s.replace('"', "\"\""))
+                } else {
+                    s.to_string()
+                }
+            } else {
+                s.replace('\\', "\\\\")
+                    .replace('\t', "\\t")
+                    .replace('\n', "\\n")
+                    .replace('\r', "\\r")
+            }
+        };
+
+        let separator = separator.to_string();
+        let mut result = self
+            .columns
+            .iter()
+            .map(|c| escape(&c.name))
+            .collect::<Vec<_>>()
+            .join(&separator);
+
+        for row in &self.values {
+            result.push('\n');
+            let row = row.iter().map(|v| escape(&cell_text(v))).collect::<Vec<_>>();
+            result.push_str(&row.join(&separator));
+        }
+        result
+    }
+
+    /// Render as a Markdown table
+    pub fn to_markdown(&self) -> String {
+        let escape = |s: &str| s.replace('|', "\\|").replace(['\n', '\r'], " ");
+
+        let header = self.columns.iter().map(|c| escape(&c.name)).collect::<Vec<_>>();
+        let mut result = format!("| {} |\n|{}", header.join(" | "), "---|".repeat(header.len()));
+
+        for row in &self.values {
+            let row = row.iter().map(|v| escape(&cell_text(v))).collect::<Vec<_>>();
+            result.push_str(&format!("\n| {} |", row.join(" | ")));
+        }
+        result
+    }
+}
+
+/// Text representation of an ES|QL value in a table cell. Multi-valued fields are output as JSON arrays.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> EsqlQueryResponse {
+        serde_json::from_value(json!({
+            "columns": [
+                {"name": "host", "type": "keyword"},
+                {"name": "count", "type": "long"},
+                {"name": "tags", "type": "keyword"}
+            ],
+            "values": [
+                ["a,b", 1, ["x", "y"]],
+                ["c|d\te", null, "z"]
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn esql_csv() {
+        assert_eq!(
+            response().to_delimited(','),
+            "host,count,tags\n\"a,b\",1,\"[\"\"x\"\",\"\"y\"\"]\"\nc|d\te,,z"
+        );
+    }
+
+    #[test]
+    fn esql_tsv() {
+        assert_eq!(
+            response().to_delimited('\t'),
+            "host\tcount\ttags\na,b\t1\t[\"x\",\"y\"]\nc|d\\te\t\tz"
+        );
+    }
+
+    #[test]
+    fn esql_markdown() {
+        assert_eq!(
+            response().to_markdown(),
+            "| host | count | tags |\n|---|---|---|\n| a,b | 1 | [\"x\",\"y\"] |\n| c\\|d\te |  | z |"
+        );
+        assert_eq!(
+            response().column_types(),
+            "Columns: host (keyword), count (long), tags (keyword)"
+        );
+    }
+
+    #[test]
+    fn search_hit_metadata() {
+        let response = || -> SearchResult {
+            serde_json::from_value(json!({
+                "hits": {
+                    "total": {"value": 1},
+                    "hits": [{
+                        "_index": "logs",
+                        "_id": "abc",
+                        "_score": 1.5,
+                        "_source": {"message": "disk full"},
+                        "highlight": {"message": ["<em>disk</em> full"]}
+                    }]
+                }
+            }))
+            .unwrap()
+        };
+
+        let contents = response().into_contents(false).unwrap();
+        assert_eq!(contents[1].as_text().unwrap().text, r#"[{"message":"disk full"}]"#);
+
+        let contents = response().into_contents(true).unwrap();
+        let hits: Value = serde_json::from_str(&contents[1].as_text().unwrap().text).unwrap();
+        assert_eq!(
+            hits,
+            json!([{
+                "_index": "logs",
+                "_id": "abc",
+                "_score": 1.5,
+                "_source": {"message": "disk full"},
+                "highlight": {"message": ["<em>disk</em> full"]}
+            }])
+        );
+    }
+
+    #[test]
+    fn only_data_stream_indices() {
+        let indices: Vec<CatIndexResponse> = serde_json::from_value(json!([
+            {"index": ".ds-logs-2025.06.01-000001", "status": "open", "docs.count": "10"},
+            {"index": ".ds-logs-2025.06.02-000002", "status": "open", "docs.count": "5"}
+        ]))
+        .unwrap();
+        let streams: Vec<DataStream> = serde_json::from_value(json!([{
+            "name": "logs",
+            "generation": 2,
+            "indices": [
+                {"index_name": ".ds-logs-2025.06.01-000001"},
+                {"index_name": ".ds-logs-2025.06.02-000002"}
+            ]
+        }]))
+        .unwrap();
+
+        // No empty list of other indices
+        let results = group_by_data_stream(indices, streams).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].as_text().unwrap().text, "Data streams:");
+    }
+}
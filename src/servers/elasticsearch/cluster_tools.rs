@@ -0,0 +1,464 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tools to diagnose the health of the cluster.
+
+use crate::servers::elasticsearch::base_tools::EsBaseTools;
+use crate::servers::elasticsearch::errors::ElasticsearchError;
+use crate::servers::elasticsearch::read_json;
+use elasticsearch::cluster::ClusterHealthParts;
+use elasticsearch::params::Level;
+use indexmap::IndexMap;
+use rmcp::RoleServer;
+use rmcp::handler::server::tool::Parameters;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::service::RequestContext;
+use rmcp_macros::{tool, tool_router};
+use serde::{Deserialize, Serialize};
+use serde_aux::prelude::*;
+use serde_json::json;
+
+/// Maximum number of pending tasks listed
+const MAX_PENDING_TASKS: usize = 10;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct ClusterHealthParams {
+    /// Index pattern to restrict the health to (optional)
+    index: Option<String>,
+
+    /// Set to true to include the health of each index (optional). Without `index`, only indices
+    /// that are not green are listed.
+    index_details: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct ExplainAllocationParams {
+    /// Index of the shard to explain (optional). If not set, the first unassigned shard found is explained.
+    index: Option<String>,
+
+    /// Number of the shard to explain (optional, default 0)
+    shard: Option<u64>,
+
+    /// True to explain the primary shard, false for a replica (optional, default false)
+    primary: Option<bool>,
+}
+
+#[tool_router(router = cluster_tool_router, vis = "pub(super)")]
+impl EsBaseTools {
+    //---------------------------------------------------------------------------------------------
+    /// Tool: cluster health overview
+    #[tool(
+        description = "Get the health of the Elasticsearch cluster (green, yellow or red) with shard counts, nodes and their resource usage (heap, disk, cpu), and pending cluster tasks. Optionally includes the health of each index.",
+        annotations(title = "Get ES cluster health", read_only_hint = true)
+    )]
+    async fn cluster_health(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(ClusterHealthParams { index, index_details }): Parameters<ClusterHealthParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let index_details = index_details.unwrap_or(false);
+
+        let indices: [&str; 1];
+        let parts = match &index {
+            Some(index) => {
+                indices = [index];
+                ClusterHealthParts::Index(&indices)
+            }
+            None => ClusterHealthParts::None,
+        };
+        let level = if index_details { Level::Indices } else { Level::Cluster };
+        let response = es_client.cluster().health(parts).level(level).send().await;
+        let health: ClusterHealthResponse = read_json(response).await?;
+
+        let response = es_client
+            .cat()
+            .nodes()
+            .h(&[
+                "name",
+                "node.role",
+                "master",
+                "heap.percent",
+                "ram.percent",
+                "cpu",
+                "load_1m",
+                "disk.used_percent",
+                "disk.avail",
+            ])
+            .format("json")
+            .send()
+            .await;
+        let nodes: Vec<CatNodeResponse> = read_json(response).await?;
+
+        let response = es_client.cluster().pending_tasks().send().await;
+        let pending: PendingTasksResponse = read_json(response).await?;
+
+        let mut results = vec![Content::text(health.summary(pending.tasks.len()))];
+
+        results.push(Content::text(format!("{} nodes:", nodes.len())));
+        results.push(Content::json(nodes)?);
+
+        if !pending.tasks.is_empty() {
+            let tasks = pending.tasks.into_iter().take(MAX_PENDING_TASKS).collect::<Vec<_>>();
+            results.push(Content::text("Oldest pending cluster tasks:"));
+            results.push(Content::json(tasks)?);
+        }
+
+        if index_details {
+            let mut indices = health
+                .indices
+                .into_iter()
+                .filter(|(_, i)| index.is_some() || i.status != "green")
+                .collect::<Vec<_>>();
+            // Unhealthy indices first
+            indices.sort_by(|(n1, i1), (n2, i2)| i1.severity().cmp(&i2.severity()).reverse().then(n1.cmp(n2)));
+
+            if indices.is_empty() {
+                results.push(Content::text("All indices are green."));
+            } else {
+                let indices = indices.into_iter().collect::<IndexMap<_, _>>();
+                results.push(Content::text(format!("Health of {} indices:", indices.len())));
+                results.push(Content::json(indices)?);
+            }
+        }
+
+        Ok(CallToolResult::success(results))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: explain shard allocation
+    #[tool(
+        description = "Explain why a shard is unassigned, or why it stays on its current node, with the decision of each node. Without parameters, explains the first unassigned shard found. Use get_shards to find unassigned shards.",
+        annotations(title = "Explain ES shard allocation", read_only_hint = true)
+    )]
+    async fn explain_allocation(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(ExplainAllocationParams { index, shard, primary }): Parameters<ExplainAllocationParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+
+        let request = es_client.cluster().allocation_explain();
+        let response = match &index {
+            Some(index) => {
+                let body = json!({
+                    "index": index,
+                    "shard": shard.unwrap_or(0),
+                    "primary": primary.unwrap_or(false),
+                });
+                request.body(body).send().await
+            }
+            None => request.send().await,
+        };
+
+        let explanation: AllocationExplanation = match read_json(response).await {
+            // Elasticsearch returns a 400 error when there's no unassigned shard to pick
+            Err(e) if index.is_none() && is_no_unassigned_shard(&e) => {
+                return Ok(CallToolResult::success(vec![Content::text(
+                    "There are no unassigned shards in the cluster. Provide an index, shard and primary \
+                     to explain the allocation of an assigned shard.",
+                )]));
+            }
+            result => result?,
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(explanation.summary())]))
+    }
+}
+
+/// Is this the error of an allocation explain request without a shard, when all shards are
+/// assigned? Elasticsearch only reports it as an illegal argument, the reason text is checked
+/// only if the error has no type.
+fn is_no_unassigned_shard(error: &rmcp::Error) -> bool {
+    ElasticsearchError::from_rmcp_error(error).is_some_and(|e| {
+        e.status == 400
+            && match e.type_.as_deref() {
+                Some(type_) => type_ == "illegal_argument_exception",
+                None => e.reason.to_lowercase().contains("unassigned shard"),
+            }
+    })
+}
+
+//----- Responses
+
+#[derive(Deserialize)]
+struct ClusterHealthResponse {
+    cluster_name: String,
+    status: String,
+    number_of_nodes: u64,
+    number_of_data_nodes: u64,
+    active_primary_shards: u64,
+    active_shards: u64,
+    relocating_shards: u64,
+    initializing_shards: u64,
+    unassigned_shards: u64,
+    #[serde(default)]
+    delayed_unassigned_shards: u64,
+    #[serde(default)]
+    indices: IndexMap<String, IndexHealth>,
+}
+
+impl ClusterHealthResponse {
+    fn summary(&self, pending_tasks: usize) -> String {
+        let mut summary = format!(
+            "Cluster {} is {}. {} nodes ({} data nodes). Shards: {} active ({} primaries), {} relocating, \
+             {} initializing, {} unassigned",
+            self.cluster_name,
+            self.status,
+            self.number_of_nodes,
+            self.number_of_data_nodes,
+            self.active_shards,
+            self.active_primary_shards,
+            self.relocating_shards,
+            self.initializing_shards,
+            self.unassigned_shards,
+        );
+        if self.delayed_unassigned_shards > 0 {
+            summary.push_str(&format!(" ({} delayed)", self.delayed_unassigned_shards));
+        }
+        summary.push_str(&format!(". {pending_tasks} pending cluster tasks."));
+
+        match self.status.as_str() {
+            "yellow" => summary.push_str(" Yellow means all primary shards are assigned but some replicas are not."),
+            "red" => {
+                summary.push_str(" Red means some primary shards are not assigned, and their data is unavailable.")
+            }
+            _ => {}
+        }
+        if self.unassigned_shards > 0 {
+            summary.push_str(" Use get_shards to find unassigned shards, and explain_allocation to know why.");
+        }
+        summary
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexHealth {
+    status: String,
+    number_of_shards: u64,
+    number_of_replicas: u64,
+    active_primary_shards: u64,
+    active_shards: u64,
+    relocating_shards: u64,
+    initializing_shards: u64,
+    unassigned_shards: u64,
+}
+
+impl IndexHealth {
+    fn severity(&self) -> u8 {
+        match self.status.as_str() {
+            "red" => 2,
+            "yellow" => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CatNodeResponse {
+    name: String,
+    #[serde(rename = "node.role")]
+    roles: Option<String>,
+    /// `*` for the elected master node
+    master: Option<String>,
+    #[serde(rename = "heap.percent", deserialize_with = "deserialize_option_number_from_string")]
+    heap_percent: Option<u64>,
+    #[serde(rename = "ram.percent", deserialize_with = "deserialize_option_number_from_string")]
+    ram_percent: Option<u64>,
+    #[serde(deserialize_with = "deserialize_option_number_from_string")]
+    cpu: Option<u64>,
+    #[serde(deserialize_with = "deserialize_option_number_from_string")]
+    load_1m: Option<f64>,
+    #[serde(
+        rename = "disk.used_percent",
+        deserialize_with = "deserialize_option_number_from_string"
+    )]
+    disk_used_percent: Option<f64>,
+    #[serde(rename = "disk.avail")]
+    disk_avail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PendingTasksResponse {
+    tasks: Vec<PendingTask>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PendingTask {
+    priority: String,
+    source: String,
+    time_in_queue_millis: u64,
+}
+
+#[derive(Deserialize)]
+struct AllocationExplanation {
+    index: String,
+    shard: u64,
+    primary: bool,
+    current_state: String,
+    unassigned_info: Option<UnassignedInfo>,
+    current_node: Option<NodeName>,
+    can_allocate: Option<String>,
+    allocate_explanation: Option<String>,
+    can_remain_on_current_node: Option<String>,
+    #[serde(default)]
+    can_remain_decisions: Vec<Decider>,
+    can_move_to_other_node: Option<String>,
+    move_explanation: Option<String>,
+    can_rebalance_cluster: Option<String>,
+    rebalance_explanation: Option<String>,
+    #[serde(default)]
+    node_allocation_decisions: Vec<NodeDecision>,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UnassignedInfo {
+    reason: String,
+    at: Option<String>,
+    details: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NodeName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct NodeDecision {
+    node_name: String,
+    node_decision: String,
+    #[serde(default)]
+    deciders: Vec<Decider>,
+}
+
+#[derive(Deserialize)]
+struct Decider {
+    decider: String,
+    decision: String,
+    explanation: String,
+}
+
+impl AllocationExplanation {
+    /// A plain text explanation, with the deciders that prevent allocation on each node.
+    fn summary(&self) -> String {
+        let copy = if self.primary { "primary" } else { "replica" };
+        let mut lines = vec![format!(
+            "Shard {} of index {} ({copy}) is {}.",
+            self.shard, self.index, self.current_state
+        )];
+
+        if let Some(node) = &self.current_node {
+            lines.push(format!("It is allocated to node {}.", node.name));
+        }
+        if let Some(info) = &self.unassigned_info {
+            let mut line = format!("It was unassigned because of {}", info.reason);
+            if let Some(at) = &info.at {
+                line.push_str(&format!(" at {at}"));
+            }
+            if let Some(details) = &info.details {
+                line.push_str(&format!(": {details}"));
+            }
+            lines.push(line);
+        }
+
+        let decisions = [
+            ("Can be allocated", &self.can_allocate, &self.allocate_explanation),
+            (
+                "Can remain on its current node",
+                &self.can_remain_on_current_node,
+                &None,
+            ),
+            (
+                "Can move to another node",
+                &self.can_move_to_other_node,
+                &self.move_explanation,
+            ),
+            (
+                "Can be rebalanced",
+                &self.can_rebalance_cluster,
+                &self.rebalance_explanation,
+            ),
+        ];
+        for (question, decision, explanation) in decisions {
+            if let Some(decision) = decision {
+                let mut line = format!("{question}: {decision}.");
+                if let Some(explanation) = explanation {
+                    line.push_str(&format!(" {explanation}"));
+                }
+                lines.push(line);
+            }
+        }
+        for decider in &self.can_remain_decisions {
+            lines.push(format!("- current node, {}", decider.describe()));
+        }
+
+        if !self.node_allocation_decisions.is_empty() {
+            lines.push("Decisions by node:".to_string());
+            for node in &self.node_allocation_decisions {
+                let deciders = node.deciders.iter().map(Decider::describe).collect::<Vec<_>>();
+                if deciders.is_empty() {
+                    lines.push(format!("- {}: {}", node.node_name, node.node_decision));
+                } else {
+                    lines.push(format!(
+                        "- {}: {}. {}",
+                        node.node_name,
+                        node.node_decision,
+                        deciders.join("; ")
+                    ));
+                }
+            }
+        }
+
+        if let Some(note) = &self.note {
+            lines.push(note.clone());
+        }
+        lines.join("\n")
+    }
+}
+
+impl Decider {
+    fn describe(&self) -> String {
+        format!("{} says {}: {}", self.decider, self.decision, self.explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_unassigned_shard_error() {
+        let error = |status, body: &str| ElasticsearchError::parse(status, body).into_rmcp_error();
+
+        let no_unassigned = r#"{"error": {"type": "illegal_argument_exception", "reason": "No shard was specified
+            in the request which means the response should explain a randomly-chosen unassigned shard, but there
+            are no unassigned shards in this cluster."}, "status": 400}"#;
+        assert!(is_no_unassigned_shard(&error(400, no_unassigned)));
+
+        // Without a type, e.g. from a proxy, the reason is checked
+        assert!(is_no_unassigned_shard(&error(400, "there are no unassigned shards")));
+        assert!(!is_no_unassigned_shard(&error(400, "Bad request")));
+
+        let security = r#"{"error": {"type": "security_exception", "reason": "unauthorized"}, "status": 403}"#;
+        assert!(!is_no_unassigned_shard(&error(403, security)));
+        assert!(!is_no_unassigned_shard(&rmcp::Error::invalid_params(
+            "unassigned shard",
+            None
+        )));
+    }
+}
@@ -0,0 +1,201 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Argument completion (`completion/complete`) with index and field names.
+//!
+//! MCP only defines completion for prompt and resource template arguments. Index names used by
+//! tools can be discovered through the `elasticsearch://index/{name}/...` resource templates.
+
+use crate::servers::elasticsearch::mappings::MappingResponse;
+use crate::servers::elasticsearch::{ArgumentCompletion, read_json};
+use elasticsearch::Elasticsearch;
+use elasticsearch::indices::{IndicesGetMappingParts, IndicesResolveIndexParts};
+use elasticsearch::params::ExpandWildcards;
+use rmcp::model::CompletionInfo;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long suggestions are kept before asking the cluster again
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Maximum number of values in a completion response (defined by the MCP spec)
+const MAX_VALUES: usize = 100;
+
+/// Cached names, with the time they were fetched
+type CacheEntry = (Instant, Arc<Vec<String>>);
+
+/// Completion suggestions, cached by credentials and completion kind, as different credentials
+/// may not have access to the same indices.
+#[derive(Default)]
+pub struct Completions {
+    cache: Mutex<HashMap<(u64, String), CacheEntry>>,
+}
+
+impl Completions {
+    /// Complete an argument value. `credentials` identifies the credentials used by `es_client`.
+    pub async fn complete(
+        &self,
+        es_client: &Elasticsearch,
+        credentials: u64,
+        kind: &ArgumentCompletion,
+        value: &str,
+    ) -> Result<CompletionInfo, rmcp::Error> {
+        let names = self.names(es_client, credentials, kind).await?;
+        Ok(matching(&names, value))
+    }
+
+    /// All names for a kind of argument, from the cache if they were fetched recently.
+    pub async fn names(
+        &self,
+        es_client: &Elasticsearch,
+        credentials: u64,
+        kind: &ArgumentCompletion,
+    ) -> Result<Arc<Vec<String>>, rmcp::Error> {
+        let key = (credentials, kind.cache_key());
+
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|(time, _)| time.elapsed() < CACHE_TTL)
+            .map(|(_, names)| names.clone());
+
+        if let Some(names) = cached {
+            return Ok(names);
+        }
+
+        let names = Arc::new(match kind {
+            ArgumentCompletion::Index => index_names(es_client).await?,
+            ArgumentCompletion::Field { index } => field_names(es_client, index.as_deref()).await?,
+        });
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, (time, _)| time.elapsed() < CACHE_TTL);
+        cache.insert(key, (Instant::now(), names.clone()));
+        Ok(names)
+    }
+}
+
+impl ArgumentCompletion {
+    fn cache_key(&self) -> String {
+        match self {
+            ArgumentCompletion::Index => "index".to_string(),
+            ArgumentCompletion::Field { index } => format!("field:{}", index.as_deref().unwrap_or("*")),
+        }
+    }
+}
+
+/// Names starting with the value come first, followed by names that contain it. Hidden names
+/// (starting with a dot) are only listed if the value starts with a dot.
+fn matching(names: &[String], value: &str) -> CompletionInfo {
+    let value = value.to_lowercase();
+    let (mut values, others): (Vec<_>, Vec<_>) = names
+        .iter()
+        .filter(|name| value.starts_with('.') || !name.starts_with('.'))
+        .filter(|name| name.to_lowercase().contains(&value))
+        .cloned()
+        .partition(|name| name.to_lowercase().starts_with(&value));
+    values.extend(others);
+
+    let total = values.len();
+    values.truncate(MAX_VALUES);
+    CompletionInfo {
+        values,
+        total: Some(total as u32),
+        has_more: Some(total > MAX_VALUES),
+    }
+}
+
+#[derive(Deserialize)]
+struct ResolveIndexResponse {
+    #[serde(default)]
+    indices: Vec<ResolvedName>,
+    #[serde(default)]
+    aliases: Vec<ResolvedName>,
+    #[serde(default)]
+    data_streams: Vec<ResolvedName>,
+}
+
+#[derive(Deserialize)]
+struct ResolvedName {
+    name: String,
+}
+
+/// Index, alias and data stream names, including hidden ones.
+async fn index_names(es_client: &Elasticsearch) -> Result<Vec<String>, rmcp::Error> {
+    let response = es_client
+        .indices()
+        .resolve_index(IndicesResolveIndexParts::Name(&["*"]))
+        .expand_wildcards(&[ExpandWildcards::All])
+        .send()
+        .await;
+    let response: ResolveIndexResponse = read_json(response).await?;
+
+    let names = response
+        .indices
+        .into_iter()
+        .chain(response.aliases)
+        .chain(response.data_streams)
+        .map(|n| n.name)
+        .collect::<BTreeSet<_>>();
+
+    Ok(names.into_iter().collect())
+}
+
+/// Field names of the indices matching a pattern (all indices by default).
+async fn field_names(es_client: &Elasticsearch, index: Option<&str>) -> Result<Vec<String>, rmcp::Error> {
+    let index = index.unwrap_or("*");
+    let response = es_client
+        .indices()
+        .get_mapping(IndicesGetMappingParts::Index(&[index]))
+        .send()
+        .await;
+    let response: MappingResponse = read_json(response).await?;
+
+    let names = response
+        .values()
+        .flat_map(|m| m.mappings.flatten())
+        .map(|field| field.path)
+        .collect::<BTreeSet<_>>();
+    Ok(names.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_completion() {
+        let names = ["host", "host.name", "host.name.keyword", "hostname", "message"].map(String::from);
+
+        let result = matching(&names, "NAME");
+        assert_eq!(result.values, ["host.name", "host.name.keyword", "hostname"]);
+        assert_eq!(result.values.len(), result.total.unwrap() as usize);
+
+        let result = matching(&names, "host");
+        assert_eq!(result.values[0], "host");
+    }
+
+    #[test]
+    fn hidden_index_completion() {
+        let names = [".logs-internal", "logs"].map(String::from);
+        assert_eq!(matching(&names, "logs").values, ["logs"]);
+        assert_eq!(matching(&names, ".lo").values, [".logs-internal"]);
+    }
+}
@@ -0,0 +1,150 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Custom tools defined in the `tools.custom` section of the configuration file.
+
+use crate::servers::elasticsearch::base_tools::{EsBaseTools, EsqlQueryRequest, EsqlQueryResponse, SearchResult};
+use crate::servers::elasticsearch::{CustomTool, EsqlTool, SearchTemplate, SearchTemplateTool, ToolBase, read_json};
+use elasticsearch::SearchTemplateParts;
+use futures::FutureExt;
+use rmcp::handler::server::tool::{ToolCallContext, ToolRoute};
+use rmcp::model::{CallToolResult, JsonObject, Tool, ToolAnnotations};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Create the tool routes for custom tools.
+pub fn routes(tools: HashMap<String, CustomTool>) -> Vec<ToolRoute<EsBaseTools>> {
+    let mut routes = Vec::new();
+    for (name, tool) in tools.into_iter() {
+        match tool {
+            CustomTool::Esql(esql) => routes.push(esql_route(name, esql)),
+            CustomTool::SearchTemplate(template) => routes.push(search_template_route(name, template)),
+        }
+    }
+    routes
+}
+
+/// Tool definition for a custom tool. All parameters are required, as they are all used
+/// by the query.
+fn tool_attr(name: String, base: &ToolBase) -> Tool {
+    let required = base.parameters.keys().collect::<Vec<_>>();
+    let schema = json!({
+        "type": "object",
+        "properties": base.parameters,
+        "required": required,
+    });
+
+    let annotations = base.annotations.clone().unwrap_or_else(|| ToolAnnotations {
+        read_only_hint: Some(true),
+        ..Default::default()
+    });
+
+    Tool {
+        name: name.into(),
+        description: Some(base.description.clone().into()),
+        input_schema: Arc::new(rmcp::model::object(schema)),
+        annotations: Some(annotations),
+    }
+}
+
+/// Get the tool arguments in the order of the parameter definitions, failing if one is missing.
+fn arguments<'a>(base: &'a ToolBase, args: &'a Option<JsonObject>) -> Result<Vec<(&'a str, Value)>, rmcp::Error> {
+    base.parameters
+        .keys()
+        .map(|name| {
+            args.as_ref()
+                .and_then(|args| args.get(name))
+                .map(|value| (name.as_str(), value.clone()))
+                .ok_or_else(|| rmcp::Error::invalid_params(format!("missing parameter '{name}'"), None))
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------
+// ES|QL
+
+fn esql_route(name: String, tool: EsqlTool) -> ToolRoute<EsBaseTools> {
+    let attr = tool_attr(name, &tool.base);
+    let tool = Arc::new(tool);
+    ToolRoute::new_dyn(attr, move |ctx: ToolCallContext<'_, EsBaseTools>| {
+        let tool = tool.clone();
+        async move { call_esql(ctx, &tool).await }.boxed()
+    })
+}
+
+async fn call_esql(ctx: ToolCallContext<'_, EsBaseTools>, tool: &EsqlTool) -> Result<CallToolResult, rmcp::Error> {
+    let params = arguments(&tool.base, &ctx.arguments)?;
+
+    let request = EsqlQueryRequest {
+        query: tool.query.clone(),
+        // Queries without parameters are sent without a `params` list
+        params: (!params.is_empty()).then(|| EsqlQueryRequest::named_params(params)),
+        ..Default::default()
+    };
+
+    let es_client = ctx.service.es_client.get(ctx.request_context);
+    let response = es_client.esql().query().body(request).send().await;
+    let response: EsqlQueryResponse = read_json(response).await?;
+
+    let mut results = response.partial_warning().into_iter().collect::<Vec<_>>();
+    results.extend(tool.format.format(response)?);
+    Ok(CallToolResult::success(results))
+}
+
+//-------------------------------------------------------------------------------------------------
+// Search templates
+
+fn search_template_route(name: String, tool: SearchTemplateTool) -> ToolRoute<EsBaseTools> {
+    let attr = tool_attr(name, &tool.base);
+    let tool = Arc::new(tool);
+    ToolRoute::new_dyn(attr, move |ctx: ToolCallContext<'_, EsBaseTools>| {
+        let tool = tool.clone();
+        async move { call_search_template(ctx, &tool).await }.boxed()
+    })
+}
+
+async fn call_search_template(
+    ctx: ToolCallContext<'_, EsBaseTools>,
+    tool: &SearchTemplateTool,
+) -> Result<CallToolResult, rmcp::Error> {
+    let params = arguments(&tool.base, &ctx.arguments)?
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect::<Map<_, _>>();
+
+    let mut request = json!({ "params": params });
+    match &tool.template {
+        SearchTemplate::TemplateId(id) => request["id"] = json!(id),
+        SearchTemplate::Template(source) => request["source"] = source.clone(),
+    }
+
+    let indices: [&str; 1];
+    let parts = match &tool.index {
+        Some(index) => {
+            indices = [index];
+            SearchTemplateParts::Index(&indices)
+        }
+        None => SearchTemplateParts::None,
+    };
+
+    let es_client = ctx.service.es_client.get(ctx.request_context);
+    let response = es_client.search_template(parts).body(request).send().await;
+    let response: SearchResult = read_json(response).await?;
+
+    Ok(CallToolResult::success(response.into_contents(false)?))
+}
@@ -0,0 +1,413 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tools to look at documents: random samples, and documents by id.
+
+use crate::servers::elasticsearch::base_tools::{EsBaseTools, SearchResult};
+use crate::servers::elasticsearch::errors::ElasticsearchError;
+use crate::servers::elasticsearch::{internal_error, read_json};
+use elasticsearch::{GetParts, MgetParts, SearchParts};
+use rmcp::RoleServer;
+use rmcp::handler::server::tool::Parameters;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::service::RequestContext;
+use rmcp_macros::{tool, tool_router};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_SAMPLE_SIZE: u64 = 5;
+const MAX_SAMPLE_SIZE: u64 = 50;
+const DEFAULT_MAX_STRING_LENGTH: usize = 200;
+const DEFAULT_MAX_ARRAY_LENGTH: usize = 5;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct SampleDocumentsParams {
+    /// Name of the Elasticsearch index, alias or index pattern
+    index: String,
+
+    /// Number of documents (optional, default 5, at most 50)
+    size: Option<u64>,
+
+    /// Fields to return, wildcards allowed (optional, default all fields)
+    fields: Option<Vec<String>>,
+
+    /// Strings longer than this are truncated (optional, default 200)
+    max_string_length: Option<usize>,
+
+    /// Arrays longer than this are truncated (optional, default 5)
+    max_array_length: Option<usize>,
+
+    /// Seed of the random sample, to get the same documents again (optional, random by default)
+    seed: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct GetDocumentParams {
+    /// Name of the Elasticsearch index or alias
+    index: String,
+
+    /// Id of the document
+    id: String,
+
+    /// Routing value, if the document was indexed with one (optional)
+    routing: Option<String>,
+
+    /// Fields of `_source` to return, wildcards allowed (optional, default all fields)
+    source_includes: Option<Vec<String>>,
+
+    /// Fields of `_source` to exclude, wildcards allowed (optional)
+    source_excludes: Option<Vec<String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct MultiGetParams {
+    /// Name of the Elasticsearch index or alias
+    index: String,
+
+    /// Ids of the documents
+    ids: Vec<String>,
+
+    /// Routing value, if the documents were indexed with one (optional)
+    routing: Option<String>,
+
+    /// Fields of `_source` to return, wildcards allowed (optional, default all fields)
+    source_includes: Option<Vec<String>>,
+
+    /// Fields of `_source` to exclude, wildcards allowed (optional)
+    source_excludes: Option<Vec<String>>,
+}
+
+#[tool_router(router = document_tool_router, vis = "pub(super)")]
+impl EsBaseTools {
+    //---------------------------------------------------------------------------------------------
+    /// Tool: random sample of documents
+    #[tool(
+        description = "Get a random sample of documents from an index, to discover what documents look like. Long strings and arrays are truncated. Prefer this to a match_all search, which returns the same, often large, documents. All documents are scored, which can be slow on very large indices.",
+        annotations(title = "Sample ES documents", read_only_hint = true)
+    )]
+    async fn sample_documents(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(SampleDocumentsParams {
+            index,
+            size,
+            fields,
+            max_string_length,
+            max_array_length,
+            seed,
+        }): Parameters<SampleDocumentsParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let size = size.unwrap_or(DEFAULT_SAMPLE_SIZE).min(MAX_SAMPLE_SIZE);
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos()
+        });
+
+        // Every document is scored, so the cost grows with the index size. Scores are computed from
+        // `_seq_no` doc values, which is cheaper than the default of loading `_id` fielddata.
+        // A `random_sampler` aggregation would visit fewer documents, but top hits in a sample
+        // favor the first documents of each shard, so the sample wouldn't be uniform.
+        let mut body = json!({
+            "size": size,
+            "query": {
+                "function_score": {
+                    "query": { "match_all": {} },
+                    "random_score": { "seed": seed, "field": "_seq_no" },
+                    "boost_mode": "replace"
+                }
+            }
+        });
+        if let Some(fields) = fields {
+            body["_source"] = json!(fields);
+        }
+
+        let response = es_client.search(SearchParts::Index(&[&index])).body(body).send().await;
+        let mut response: SearchResult = read_json(response).await?;
+
+        let limits = TrimLimits {
+            string_length: max_string_length.unwrap_or(DEFAULT_MAX_STRING_LENGTH),
+            array_length: max_array_length.unwrap_or(DEFAULT_MAX_ARRAY_LENGTH),
+        };
+        for hit in &mut response.hits.hits {
+            limits.trim(&mut hit.source);
+        }
+
+        Ok(CallToolResult::success(response.into_contents(false)?))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: get a document by id
+    #[tool(
+        description = "Get a document by its id, e.g. to follow a reference found in another document.",
+        annotations(title = "Get ES document", read_only_hint = true)
+    )]
+    async fn get_document(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(GetDocumentParams {
+            index,
+            id,
+            routing,
+            source_includes,
+            source_excludes,
+        }): Parameters<GetDocumentParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+
+        let mut request = es_client.get(GetParts::IndexId(&index, &id));
+        if let Some(routing) = &routing {
+            request = request.routing(routing);
+        }
+        let includes = source_fields(source_includes.as_deref());
+        if !includes.is_empty() {
+            request = request._source_includes(&includes);
+        }
+        let excludes = source_fields(source_excludes.as_deref());
+        if !excludes.is_empty() {
+            request = request._source_excludes(&excludes);
+        }
+
+        // A missing document is a 404 with a regular get response, unlike a missing index
+        let response = request.send().await.map_err(internal_error)?;
+        let doc: GetResponse = if response.status_code().as_u16() == 404 {
+            let body = response.text().await.map_err(internal_error)?;
+            match missing_document(&body) {
+                Some(doc) => doc,
+                None => return Err(ElasticsearchError::parse(404, &body).into_rmcp_error()),
+            }
+        } else {
+            read_json(Ok(response)).await?
+        };
+
+        if !doc.found {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "Document {id} not found in {index}."
+            ))]));
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Document {} in index {}:", doc.id, doc.index)),
+            Content::json(doc.source.unwrap_or_default())?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: get documents by id
+    #[tool(
+        description = "Get several documents by their ids in a single request.",
+        annotations(title = "Get ES documents", read_only_hint = true)
+    )]
+    async fn multi_get(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(MultiGetParams {
+            index,
+            ids,
+            routing,
+            source_includes,
+            source_excludes,
+        }): Parameters<MultiGetParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+
+        let mut request = es_client.mget(MgetParts::Index(&index));
+        if let Some(routing) = &routing {
+            request = request.routing(routing);
+        }
+        let includes = source_fields(source_includes.as_deref());
+        if !includes.is_empty() {
+            request = request._source_includes(&includes);
+        }
+        let excludes = source_fields(source_excludes.as_deref());
+        if !excludes.is_empty() {
+            request = request._source_excludes(&excludes);
+        }
+
+        let response = request.body(json!({ "ids": ids })).send().await;
+        let response: MultiGetResponse = read_json(response).await?;
+
+        let mut found = Vec::new();
+        let mut not_found = Vec::new();
+        let mut errors = Vec::new();
+        for doc in response.docs {
+            match doc {
+                MultiGetDoc::Error { id, error } => errors.push(format!("{id}: {}", error.message())),
+                MultiGetDoc::Doc(doc) if doc.found => found.push(doc),
+                MultiGetDoc::Doc(doc) => not_found.push(doc.id),
+            }
+        }
+
+        let mut results = vec![Content::text(format!(
+            "Found {} of {} documents.",
+            found.len(),
+            ids.len()
+        ))];
+        if !found.is_empty() {
+            results.push(Content::json(found)?);
+        }
+        if !not_found.is_empty() {
+            results.push(Content::text(format!("Not found: {}", not_found.join(", "))));
+        }
+        if !errors.is_empty() {
+            results.push(Content::text(format!("Errors:\n{}", errors.join("\n"))));
+        }
+
+        Ok(CallToolResult::success(results))
+    }
+}
+
+/// `_source` includes or excludes, as the string slices expected by request builders
+fn source_fields(fields: Option<&[String]>) -> Vec<&str> {
+    fields.unwrap_or_default().iter().map(String::as_str).collect()
+}
+
+/// The get response in the body of a 404 for a missing document. Other 404 bodies, e.g. a missing
+/// index or a proxy error page, aren't get responses.
+fn missing_document(body: &str) -> Option<GetResponse> {
+    serde_json::from_str::<GetResponse>(body).ok().filter(|doc| !doc.found)
+}
+
+//----- Get responses
+
+#[derive(Serialize, Deserialize)]
+struct GetResponse {
+    #[serde(rename = "_index")]
+    index: String,
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(default, skip_serializing)]
+    found: bool,
+    #[serde(rename = "_routing", skip_serializing_if = "Option::is_none")]
+    routing: Option<String>,
+    #[serde(rename = "_source", skip_serializing_if = "Option::is_none")]
+    source: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct MultiGetResponse {
+    docs: Vec<MultiGetDoc>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MultiGetDoc {
+    Error {
+        #[serde(rename = "_id")]
+        id: String,
+        error: MultiGetError,
+    },
+    Doc(GetResponse),
+}
+
+#[derive(Deserialize)]
+struct MultiGetError {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    reason: Option<String>,
+}
+
+impl MultiGetError {
+    fn message(&self) -> String {
+        match (&self.type_, &self.reason) {
+            (Some(type_), Some(reason)) => format!("{reason} ({type_})"),
+            (_, Some(reason)) => reason.clone(),
+            (Some(type_), None) => type_.clone(),
+            (None, None) => "unknown error".to_string(),
+        }
+    }
+}
+
+/// Limits applied to documents so that they don't fill the model's context
+struct TrimLimits {
+    string_length: usize,
+    array_length: usize,
+}
+
+impl TrimLimits {
+    /// Truncate long strings and arrays, recursively. Truncation is noted in the value.
+    fn trim(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => {
+                let len = s.chars().count();
+                if len > self.string_length {
+                    let truncated = s.chars().take(self.string_length).collect::<String>();
+                    *s = format!("{truncated}... ({} more characters)", len - self.string_length);
+                }
+            }
+            Value::Array(items) => {
+                let len = items.len();
+                if len > self.array_length {
+                    items.truncate(self.array_length);
+                    items.push(Value::String(format!("... ({} more items)", len - self.array_length)));
+                }
+                for item in items.iter_mut().take(self.array_length) {
+                    self.trim(item);
+                }
+            }
+            Value::Object(obj) => {
+                for item in obj.values_mut() {
+                    self.trim(item);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_document_body() {
+        let doc = missing_document(r#"{"_index": "logs", "_id": "1", "found": false}"#);
+        assert_eq!(doc.map(|doc| doc.id), Some("1".to_string()));
+
+        // A missing index, and a 404 from something else than Elasticsearch, e.g. a proxy
+        let missing_index = r#"{"error": {"type": "index_not_found_exception", "reason": "no such index [logz]"},
+            "status": 404}"#;
+        assert!(missing_document(missing_index).is_none());
+        assert!(missing_document("<html>Not Found</html>").is_none());
+        assert!(missing_document(r#"{"error": "no handler found"}"#).is_none());
+    }
+
+    #[test]
+    fn trim_document() {
+        let limits = TrimLimits {
+            string_length: 5,
+            array_length: 2,
+        };
+        let mut doc = json!({
+            "message": "hello world",
+            "tags": ["a", "b", "c", "d"],
+            "nested": {"values": [{"text": "abcdefgh"}], "count": 3}
+        });
+        limits.trim(&mut doc);
+        assert_eq!(
+            doc,
+            json!({
+                "message": "hello... (6 more characters)",
+                "tags": ["a", "b", "... (2 more items)"],
+                "nested": {"values": [{"text": "abcde... (3 more characters)"}], "count": 3}
+            })
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Elasticsearch error responses, returned to the client as tool results so that the model can
+//! fix its request.
+//!
+//! Errors are propagated as `rmcp::Error` (so that the `?` operator can be used) carrying the
+//! parsed Elasticsearch error in their `data`. They are converted to a `CallToolResult` with
+//! `is_error: true` when a tool call returns.
+
+use rmcp::model::{CallToolResult, Content, ErrorCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Key of the Elasticsearch error in `rmcp::Error::data`
+const ES_ERROR_KEY: &str = "elasticsearch_error";
+
+/// An error returned by Elasticsearch, with the details useful to fix the request.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ElasticsearchError {
+    pub status: u16,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub reason: String,
+    pub line: Option<u64>,
+    pub col: Option<u64>,
+    pub index: Option<String>,
+    /// Reasons of the underlying errors
+    pub causes: Vec<String>,
+    /// Existing names close to a missing index
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+}
+
+/// An error object in an Elasticsearch error response
+#[derive(Deserialize)]
+struct ErrorCause {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    reason: Option<String>,
+    line: Option<u64>,
+    col: Option<u64>,
+    index: Option<String>,
+    #[serde(default)]
+    root_cause: Vec<ErrorCause>,
+    caused_by: Option<Box<ErrorCause>>,
+    #[serde(default)]
+    failed_shards: Vec<FailedShard>,
+}
+
+#[derive(Deserialize)]
+struct FailedShard {
+    reason: ErrorCause,
+}
+
+impl ElasticsearchError {
+    /// Parse an error response body. Bodies that aren't Elasticsearch errors are used as the reason.
+    pub fn parse(status: u16, body: &str) -> Self {
+        let error = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|mut v| v.get_mut("error").map(Value::take));
+
+        let cause = match error {
+            Some(Value::String(reason)) => return Self::new(status, reason),
+            Some(error) => serde_json::from_value::<ErrorCause>(error).ok(),
+            None => None,
+        };
+        let Some(mut cause) = cause else {
+            let reason = if body.is_empty() { "no details" } else { body };
+            return Self::new(status, reason.to_string());
+        };
+
+        // Root causes and shard failures have the most specific details (e.g. parsing errors in
+        // a search that failed on all shards).
+        let detail = if !cause.root_cause.is_empty() {
+            Some(cause.root_cause.swap_remove(0))
+        } else if !cause.failed_shards.is_empty() {
+            Some(cause.failed_shards.swap_remove(0).reason)
+        } else {
+            None
+        };
+        let detail = detail.as_ref().unwrap_or(&cause);
+        let reason = detail.reason.clone().unwrap_or_else(|| "no details".to_string());
+
+        let mut causes = Vec::new();
+        let mut next = Some(&cause);
+        while let Some(c) = next {
+            if let Some(r) = &c.reason
+                && *r != reason
+                && !causes.contains(r)
+            {
+                causes.push(r.clone());
+            }
+            next = c.caused_by.as_deref();
+        }
+
+        ElasticsearchError {
+            status,
+            type_: detail.type_.clone().or_else(|| cause.type_.clone()),
+            line: detail.line.or(cause.line),
+            col: detail.col.or(cause.col),
+            index: detail.index.clone().or_else(|| cause.index.clone()),
+            reason,
+            causes,
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn new(status: u16, reason: String) -> Self {
+        ElasticsearchError {
+            status,
+            type_: None,
+            reason,
+            line: None,
+            col: None,
+            index: None,
+            causes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// A hint for the model on how to fix the request, depending on the error type
+    fn hint(&self) -> Option<&'static str> {
+        let hint = match self.type_.as_deref()? {
+            "index_not_found_exception" => "Use list_indices to find existing indices.",
+            "parsing_exception" | "x_content_parse_exception" | "json_parse_exception" => {
+                "The query has a syntax error, fix it at the location indicated."
+            }
+            "verification_exception" => {
+                "The ES|QL query is invalid, check field and function names against the index mappings."
+            }
+            "search_context_missing_exception" => "The search cursor has expired, run the search again.",
+            "security_exception" => "The credentials used are not allowed to perform this operation.",
+            "illegal_argument_exception" => "A request parameter is invalid, check field names and types.",
+            _ => return None,
+        };
+        Some(hint)
+    }
+
+    /// Explanation of the error, as sent to the client
+    pub fn message(&self) -> String {
+        let mut message = match &self.type_ {
+            Some(type_) => format!("Elasticsearch error ({}, {type_}): {}", self.status, self.reason),
+            None => format!("Elasticsearch error ({}): {}", self.status, self.reason),
+        };
+        match (self.line, self.col) {
+            (Some(line), Some(col)) => message.push_str(&format!("\nLocation: line {line}, column {col}")),
+            (Some(line), None) => message.push_str(&format!("\nLocation: line {line}")),
+            _ => {}
+        }
+        for cause in &self.causes {
+            message.push_str(&format!("\nCaused by: {cause}"));
+        }
+        if !self.suggestions.is_empty() {
+            message.push_str(&format!("\nDid you mean: {}?", self.suggestions.join(", ")));
+        }
+        if let Some(hint) = self.hint() {
+            message.push_str(&format!("\n{hint}"));
+        }
+        message
+    }
+
+    /// Convert to an `rmcp::Error` that can be propagated with `?`
+    pub fn into_rmcp_error(self) -> rmcp::Error {
+        let code = if self.status < 500 {
+            ErrorCode::INVALID_PARAMS
+        } else {
+            ErrorCode::INTERNAL_ERROR
+        };
+        let message = self.message();
+        let data = serde_json::to_value(&self)
+            .ok()
+            .map(|v| serde_json::json!({ ES_ERROR_KEY: v }));
+        rmcp::Error::new(code, message, data)
+    }
+
+    /// The name of the missing index, for index not found errors. ES|QL reports them as
+    /// verification errors, with the index only in the reason, e.g. "line 1:6: Unknown index [logz]".
+    pub fn missing_index(&self) -> Option<&str> {
+        match self.type_.as_deref() {
+            Some("index_not_found_exception") => self.index.as_deref(),
+            Some("verification_exception") => {
+                let (_, rest) = self.reason.split_once("Unknown index [")?;
+                rest.split_once(']').map(|(index, _)| index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the Elasticsearch error carried by an `rmcp::Error`, if any
+    pub fn from_rmcp_error(error: &rmcp::Error) -> Option<Self> {
+        let value = error.data.as_ref()?.get(ES_ERROR_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn into_tool_result(self) -> CallToolResult {
+        CallToolResult::error(vec![Content::text(self.message())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_search_error() {
+        let body = r#"{
+            "error": {
+                "root_cause": [
+                    {"type": "parsing_exception", "reason": "unknown query [mtch]", "line": 1, "col": 20}
+                ],
+                "type": "parsing_exception",
+                "reason": "unknown query [mtch]",
+                "line": 1,
+                "col": 20,
+                "caused_by": {"type": "named_object_not_found_exception", "reason": "[1:20] unknown field [mtch]"}
+            },
+            "status": 400
+        }"#;
+        let error = ElasticsearchError::parse(400, body);
+        assert_eq!(error.type_.as_deref(), Some("parsing_exception"));
+        assert_eq!((error.line, error.col), (Some(1), Some(20)));
+        assert_eq!(
+            error.message(),
+            "Elasticsearch error (400, parsing_exception): unknown query [mtch]\n\
+            Location: line 1, column 20\n\
+            Caused by: [1:20] unknown field [mtch]\n\
+            The query has a syntax error, fix it at the location indicated."
+        );
+
+        let rmcp_error = error.into_rmcp_error();
+        let error = ElasticsearchError::from_rmcp_error(&rmcp_error).unwrap();
+        assert_eq!(error.into_tool_result().is_error, Some(true));
+    }
+
+    #[test]
+    fn parse_other_errors() {
+        let body = r#"{"error": {"type": "verification_exception",
+            "reason": "Found 1 problem\nline 1:23: Unknown column [foo]"}, "status": 400}"#;
+        let error = ElasticsearchError::parse(400, body);
+        assert_eq!(error.type_.as_deref(), Some("verification_exception"));
+        assert!(error.causes.is_empty());
+        assert_eq!(error.missing_index(), None);
+
+        let body = r#"{"error": {"type": "verification_exception",
+            "reason": "Found 1 problem\nline 1:6: Unknown index [logz]"}, "status": 400}"#;
+        assert_eq!(ElasticsearchError::parse(400, body).missing_index(), Some("logz"));
+
+        let error = ElasticsearchError::parse(405, r#"{"error": "Incorrect HTTP method"}"#);
+        assert_eq!(error.reason, "Incorrect HTTP method");
+
+        let error = ElasticsearchError::parse(502, "Bad gateway");
+        assert_eq!(error.message(), "Elasticsearch error (502): Bad gateway");
+
+        assert!(ElasticsearchError::from_rmcp_error(&rmcp::Error::invalid_params("bad", None)).is_none());
+    }
+}
@@ -0,0 +1,623 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tools to explore fields: their capabilities across indices, and the values they hold.
+
+use crate::servers::elasticsearch::base_tools::{EsBaseTools, SearchResult};
+use crate::servers::elasticsearch::mappings::{FieldCatalog, FlatField, MappingResponse};
+use crate::servers::elasticsearch::read_json;
+use elasticsearch::indices::IndicesGetMappingParts;
+use elasticsearch::{FieldCapsParts, SearchParts, TermsEnumParts};
+use indexmap::IndexMap;
+use rmcp::RoleServer;
+use rmcp::handler::server::tool::Parameters;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::service::RequestContext;
+use rmcp_macros::{tool, tool_router};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Pseudo-type of fields in indices where they don't exist, with `include_unmapped`
+const UNMAPPED: &str = "unmapped";
+
+/// Default number of top values returned by `describe_field`
+const DEFAULT_TOP: u64 = 10;
+
+const PERCENTS: [f64; 7] = [1.0, 5.0, 25.0, 50.0, 75.0, 95.0, 99.0];
+
+/// Numeric fields with at most this many distinct values, like status codes, also have top values
+const MAX_NUMERIC_TOP_CARDINALITY: u64 = 100;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct FieldCapsParams {
+    /// Name of the Elasticsearch index, alias or index pattern
+    index: String,
+
+    /// Field names or wildcard patterns, e.g. `host.*` (optional, default all fields)
+    fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct DescribeFieldParams {
+    /// Name of the Elasticsearch index, alias or index pattern
+    index: String,
+
+    /// Dotted path of the field, e.g. `host.name`
+    field: String,
+
+    /// Number of top values to return (optional, default 10)
+    top: Option<u64>,
+
+    /// For keyword fields, only list values starting with this prefix (optional)
+    prefix: Option<String>,
+}
+
+#[tool_router(router = field_tool_router, vis = "pub(super)")]
+impl EsBaseTools {
+    //---------------------------------------------------------------------------------------------
+    /// Tool: field capabilities
+    #[tool(
+        description = "Get the capabilities of fields across an index pattern: their type, whether they are searchable and aggregatable (i.e. usable in aggregations and ES|QL STATS ... BY), and which indices have a different type or don't have the field. Much cheaper than get_mappings on many indices.",
+        annotations(title = "Get ES field capabilities", read_only_hint = true)
+    )]
+    async fn field_caps(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(FieldCapsParams { index, fields }): Parameters<FieldCapsParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let fields = fields.unwrap_or_else(|| vec!["*".to_string()]);
+        let fields = fields.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let response = es_client
+            .field_caps(FieldCapsParts::Index(&[&index]))
+            .fields(&fields)
+            .include_unmapped(true)
+            .send()
+            .await;
+        let response: FieldCapsResponse = read_json(response).await?;
+
+        let index_count = response.indices.len();
+        let fields = summarize(response);
+
+        let conflicts = fields.values().filter(|f| f.types.is_some()).count();
+        let partial = fields.values().filter(|f| f.missing_in.is_some()).count();
+        let mut summary = format!("{} fields in {index_count} indices matching {index}.", fields.len());
+        if conflicts > 0 {
+            summary.push_str(&format!(
+                " {conflicts} fields have a type that differs between indices (type 'conflict')."
+            ));
+        }
+        if partial > 0 {
+            summary.push_str(&format!(
+                " {partial} fields are missing in some indices (see 'missing_in')."
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::json(fields)?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: describe the values of a field
+    #[tool(
+        description = "Describe the values of a field in an index, to learn what it holds before writing queries: approximate number of distinct values, top values with their counts (or values starting with a prefix), min/max/percentiles for numbers and dates, top values of numbers only if they have few distinct values, and the ratio of documents missing the field.",
+        annotations(title = "Describe ES field values", read_only_hint = true)
+    )]
+    async fn describe_field(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(DescribeFieldParams {
+            index,
+            field,
+            top,
+            prefix,
+        }): Parameters<DescribeFieldParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let top = top.unwrap_or(DEFAULT_TOP);
+
+        let response = es_client
+            .indices()
+            .get_mapping(IndicesGetMappingParts::Index(&[&index]))
+            .send()
+            .await;
+        let mappings: MappingResponse = read_json(response).await?;
+        let fields = mappings.values().flat_map(|m| m.mappings.flatten()).collect::<Vec<_>>();
+
+        let Some(target) = FieldTarget::new(&field, &fields) else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Field '{field}' doesn't exist in '{index}'. Use get_mappings or field_caps to list fields."
+            ))]));
+        };
+
+        // Aggregations fail or mix values if the field has a different type in some indices
+        let catalog = FieldCatalog::new(&mappings);
+        let conflict = [Some(&target.path), target.agg_field.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|path| Some((path, catalog.fields.get(path)?.types.as_ref()?)));
+        if let Some((path, types)) = conflict {
+            let types = types
+                .iter()
+                .map(|(type_, indices)| format!("{type_} in {}", indices.join(", ")))
+                .collect::<Vec<_>>();
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Field '{path}' has different types in indices matching '{index}': {}. \
+                 Describe it in indices that have the same type.",
+                types.join("; ")
+            ))]));
+        }
+
+        let response = es_client
+            .search(SearchParts::Index(&[&index]))
+            .body(target.request(top))
+            .send()
+            .await;
+        let response: SearchResult = read_json(response).await?;
+        let mut description = target.describe(response);
+
+        let mut prefix_ignored = false;
+        if let Some(prefix) = prefix {
+            match &target.prefix_field {
+                Some(prefix_field) => {
+                    let response = es_client
+                        .terms_enum(TermsEnumParts::Index(&[&index]))
+                        .body(json!({ "field": prefix_field, "string": prefix, "size": top }))
+                        .send()
+                        .await;
+                    let response: TermsEnumResponse = read_json(response).await?;
+                    description.top_values = None;
+                    description.values_with_prefix = Some(response.terms);
+                }
+                None => prefix_ignored = true,
+            }
+        }
+
+        let mut results = vec![Content::text(format!(
+            "Field {field} of type {} in {index}:",
+            description.type_
+        ))];
+        if prefix_ignored {
+            results.push(Content::text(
+                "The prefix was ignored: it is only supported on keyword fields and text fields with a keyword multi-field.",
+            ));
+        }
+        if description.aggregated_field.is_none() {
+            results.push(Content::text(
+                "Values of this field can't be aggregated (text fields need a keyword multi-field): only the missing ratio is available.",
+            ));
+        }
+        results.push(Content::json(description)?);
+
+        Ok(CallToolResult::success(results))
+    }
+}
+
+//----- Field capabilities
+
+#[derive(Deserialize)]
+struct FieldCapsResponse {
+    #[serde(default)]
+    indices: Vec<String>,
+    /// Capabilities of each field, by type
+    fields: BTreeMap<String, BTreeMap<String, TypeCapability>>,
+}
+
+#[derive(Deserialize)]
+struct TypeCapability {
+    #[serde(default)]
+    metadata_field: bool,
+    #[serde(default)]
+    searchable: bool,
+    #[serde(default)]
+    aggregatable: bool,
+    /// Indices having this type, only if the field has several types
+    indices: Option<Vec<String>>,
+    non_searchable_indices: Option<Vec<String>>,
+    non_aggregatable_indices: Option<Vec<String>>,
+}
+
+/// Capabilities of a field, merged across types
+#[derive(Serialize)]
+struct FieldCapability {
+    /// Field type, or `conflict` if it differs between indices
+    #[serde(rename = "type")]
+    type_: String,
+    /// Indices for each type, for conflicting fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<BTreeMap<String, Vec<String>>>,
+    /// Searchable in all indices
+    searchable: bool,
+    /// Aggregatable in all indices
+    aggregatable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_searchable_indices: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    non_aggregatable_indices: Option<Vec<String>>,
+    /// Indices that don't have this field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    missing_in: Option<Vec<String>>,
+}
+
+/// Merge the capabilities of each field's types, skipping metadata fields like `_id`.
+fn summarize(response: FieldCapsResponse) -> BTreeMap<String, FieldCapability> {
+    response
+        .fields
+        .into_iter()
+        .filter(|(_, types)| !types.values().any(|t| t.metadata_field))
+        .filter_map(|(name, mut types)| {
+            let missing_in = types.remove(UNMAPPED).map(|t| t.indices.unwrap_or_default());
+            if types.is_empty() {
+                return None;
+            }
+
+            let searchable = types.values().all(|t| t.searchable);
+            let aggregatable = types.values().all(|t| t.aggregatable);
+            let non_searchable = merge_indices(types.values().map(|t| &t.non_searchable_indices));
+            let non_aggregatable = merge_indices(types.values().map(|t| &t.non_aggregatable_indices));
+
+            let (type_, types) = if types.len() == 1 {
+                (types.into_keys().next().unwrap(), None)
+            } else {
+                let types = types
+                    .into_iter()
+                    .map(|(type_, cap)| (type_, cap.indices.unwrap_or_default()))
+                    .collect();
+                ("conflict".to_string(), Some(types))
+            };
+
+            let field = FieldCapability {
+                type_,
+                types,
+                searchable,
+                aggregatable,
+                non_searchable_indices: non_searchable,
+                non_aggregatable_indices: non_aggregatable,
+                missing_in,
+            };
+            Some((name, field))
+        })
+        .collect()
+}
+
+fn merge_indices<'a>(lists: impl Iterator<Item = &'a Option<Vec<String>>>) -> Option<Vec<String>> {
+    let merged = lists.flatten().flatten().cloned().collect::<BTreeSet<_>>();
+    (!merged.is_empty()).then(|| merged.into_iter().collect())
+}
+
+//----- Field description
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldKind {
+    Keyword,
+    Numeric,
+    Date,
+    Text,
+    Other,
+}
+
+impl FieldKind {
+    fn of(type_: &str) -> Self {
+        match type_ {
+            "keyword" | "constant_keyword" | "wildcard" | "ip" | "boolean" | "version" => FieldKind::Keyword,
+            "long" | "integer" | "short" | "byte" | "double" | "float" | "half_float" | "scaled_float"
+            | "unsigned_long" => FieldKind::Numeric,
+            "date" | "date_nanos" => FieldKind::Date,
+            "text" | "match_only_text" => FieldKind::Text,
+            _ => FieldKind::Other,
+        }
+    }
+}
+
+/// The field to describe, and the field that aggregations run on
+struct FieldTarget {
+    path: String,
+    type_: String,
+    kind: FieldKind,
+    /// The field itself, or its keyword multi-field for text fields
+    agg_field: Option<String>,
+    /// The aggregated field, if values starting with a prefix can be listed with the terms enum API
+    prefix_field: Option<String>,
+}
+
+impl FieldTarget {
+    fn new(path: &str, fields: &[FlatField]) -> Option<Self> {
+        let field = fields.iter().find(|f| f.path == path)?;
+        let kind = FieldKind::of(&field.type_);
+        let agg = match kind {
+            FieldKind::Keyword | FieldKind::Numeric | FieldKind::Date => Some(field),
+            FieldKind::Text => fields
+                .iter()
+                .find(|f| f.parent.as_deref() == Some(path) && FieldKind::of(&f.type_) == FieldKind::Keyword),
+            FieldKind::Other => None,
+        };
+        let agg_field = agg.map(|f| f.path.clone());
+        // The terms enum API doesn't support other keyword-like types such as boolean or ip
+        let prefix_field = agg
+            .filter(|f| matches!(f.type_.as_str(), "keyword" | "constant_keyword"))
+            .map(|f| f.path.clone());
+        let (kind, agg_field) = match agg_field {
+            Some(agg_field) if kind == FieldKind::Text => (FieldKind::Keyword, Some(agg_field)),
+            agg_field => (kind, agg_field),
+        };
+
+        Some(FieldTarget {
+            path: path.to_string(),
+            type_: field.type_.clone(),
+            kind,
+            agg_field,
+            prefix_field,
+        })
+    }
+
+    /// A single search request with all aggregations for this field
+    fn request(&self, top: u64) -> Value {
+        let mut aggs = Map::new();
+        aggs.insert(
+            "missing".to_string(),
+            json!({ "filter": { "bool": { "must_not": { "exists": { "field": self.path } } } } }),
+        );
+
+        if let Some(field) = &self.agg_field {
+            aggs.insert("cardinality".to_string(), json!({ "cardinality": { "field": field } }));
+            match self.kind {
+                FieldKind::Numeric | FieldKind::Date => {
+                    aggs.insert("stats".to_string(), json!({ "stats": { "field": field } }));
+                    aggs.insert(
+                        "percentiles".to_string(),
+                        json!({ "percentiles": { "field": field, "percents": PERCENTS } }),
+                    );
+                    if self.kind == FieldKind::Numeric {
+                        aggs.insert("top".to_string(), json!({ "terms": { "field": field, "size": top } }));
+                    }
+                }
+                _ => {
+                    aggs.insert("top".to_string(), json!({ "terms": { "field": field, "size": top } }));
+                }
+            }
+        }
+
+        json!({ "size": 0, "track_total_hits": true, "aggs": aggs })
+    }
+
+    fn describe(&self, mut response: SearchResult) -> FieldDescription {
+        let total = response.hits.total.map(|t| t.value).unwrap_or_default();
+        let mut agg = |name: &str| response.aggregations.shift_remove(name).unwrap_or_default();
+
+        let missing = agg("missing")["doc_count"].as_u64().unwrap_or_default();
+        let cardinality = agg("cardinality")["value"].as_u64();
+
+        // Top values of numbers are only useful for codes and enumerations, not for measures
+        let mut top = agg("top");
+        if self.kind == FieldKind::Numeric && cardinality.is_none_or(|c| c > MAX_NUMERIC_TOP_CARDINALITY) {
+            top = Value::Null;
+        }
+        let top_values = top["buckets"].as_array().map(|buckets| {
+            buckets
+                .iter()
+                .map(|b| {
+                    // Keys of boolean and date fields are numbers, with a readable `key_as_string`
+                    let value = b.get("key_as_string").unwrap_or(&b["key"]).clone();
+                    TermCount {
+                        value,
+                        count: b["doc_count"].as_u64().unwrap_or_default(),
+                    }
+                })
+                .collect()
+        });
+
+        // Dates have a readable value in `<name>_as_string`
+        let value = |stats: &Value, name: &str| {
+            match self.kind {
+                FieldKind::Date => stats.get(format!("{name}_as_string")).cloned(),
+                _ => stats.get(name).cloned(),
+            }
+            .filter(|v| !v.is_null())
+        };
+
+        let stats = agg("stats");
+        let percentiles = agg("percentiles");
+        let percentiles = percentiles.get("values").filter(|v| v.is_object()).map(|values| {
+            PERCENTS
+                .iter()
+                .filter_map(|p| Some((p.to_string(), value(values, &format!("{p:.1}"))?)))
+                .collect::<IndexMap<_, _>>()
+        });
+
+        FieldDescription {
+            type_: self.type_.clone(),
+            aggregated_field: self.agg_field.clone(),
+            total_docs: total,
+            missing_docs: missing,
+            missing_ratio: if total > 0 { missing as f64 / total as f64 } else { 0.0 },
+            cardinality,
+            top_values,
+            other_docs: top["sum_other_doc_count"].as_u64().filter(|c| *c > 0),
+            values_with_prefix: None,
+            min: value(&stats, "min"),
+            max: value(&stats, "max"),
+            avg: value(&stats, "avg"),
+            percentiles: percentiles.filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FieldDescription {
+    #[serde(rename = "type")]
+    type_: String,
+    /// Field used for aggregations, e.g. the keyword multi-field of a text field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregated_field: Option<String>,
+    total_docs: u64,
+    missing_docs: u64,
+    missing_ratio: f64,
+    /// Approximate number of distinct values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cardinality: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_values: Option<Vec<TermCount>>,
+    /// Number of documents having other values than the top values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    other_docs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values_with_prefix: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avg: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percentiles: Option<IndexMap<String, Value>>,
+}
+
+#[derive(Serialize)]
+struct TermCount {
+    value: Value,
+    count: u64,
+}
+
+#[derive(Deserialize)]
+struct TermsEnumResponse {
+    terms: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn field_caps_summary() {
+        let response: FieldCapsResponse = serde_json::from_value(json!({
+            "indices": ["logs-1", "logs-2"],
+            "fields": {
+                "_id": {"_id": {"type": "_id", "metadata_field": true, "searchable": true, "aggregatable": false}},
+                "message": {"text": {"type": "text", "searchable": true, "aggregatable": false}},
+                "status": {
+                    "keyword": {"type": "keyword", "searchable": true, "aggregatable": true, "indices": ["logs-1"]},
+                    "long": {"type": "long", "searchable": true, "aggregatable": true, "indices": ["logs-2"]}
+                },
+                "host.name": {
+                    "keyword": {"type": "keyword", "searchable": true, "aggregatable": false,
+                        "indices": ["logs-1"], "non_aggregatable_indices": ["logs-1"]},
+                    "unmapped": {"type": "unmapped", "searchable": false, "aggregatable": false, "indices": ["logs-2"]}
+                }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(summarize(response)).unwrap(),
+            json!({
+                "host.name": {"type": "keyword", "searchable": true, "aggregatable": false,
+                    "non_aggregatable_indices": ["logs-1"], "missing_in": ["logs-2"]},
+                "message": {"type": "text", "searchable": true, "aggregatable": false},
+                "status": {"type": "conflict", "types": {"keyword": ["logs-1"], "long": ["logs-2"]},
+                    "searchable": true, "aggregatable": true}
+            })
+        );
+    }
+
+    #[test]
+    fn prefix_field() {
+        let field = |path: &str, type_: &str, parent: Option<&str>| FlatField {
+            path: path.to_string(),
+            type_: type_.to_string(),
+            parent: parent.map(str::to_string),
+            runtime: false,
+        };
+        let fields = [
+            field("message", "text", None),
+            field("message.keyword", "keyword", Some("message")),
+            field("enabled", "boolean", None),
+            field("bytes", "long", None),
+        ];
+        let prefix_field = |path: &str| FieldTarget::new(path, &fields).unwrap().prefix_field;
+
+        assert_eq!(prefix_field("message").as_deref(), Some("message.keyword"));
+        assert_eq!(prefix_field("message.keyword").as_deref(), Some("message.keyword"));
+        assert_eq!(prefix_field("enabled"), None);
+        assert_eq!(prefix_field("bytes"), None);
+    }
+
+    #[test]
+    fn describe_date_field() {
+        let fields = [FlatField {
+            path: "@timestamp".to_string(),
+            type_: "date".to_string(),
+            parent: None,
+            runtime: false,
+        }];
+        let target = FieldTarget::new("@timestamp", &fields).unwrap();
+        let request = target.request(10);
+        assert!(request["aggs"]["percentiles"].is_object());
+        assert!(request["aggs"].get("top").is_none());
+
+        let response: SearchResult = serde_json::from_value(json!({
+            "hits": {"total": {"value": 10}, "hits": []},
+            "aggregations": {
+                "missing": {"doc_count": 0},
+                "cardinality": {"value": 10},
+                "stats": {"min": 1.0, "max": 2.0, "min_as_string": "2025-01-01", "max_as_string": "2025-01-02"},
+                "percentiles": {"values": {"50.0": 1.5, "50.0_as_string": "2025-01-01T12:00"}}
+            }
+        }))
+        .unwrap();
+        let description = serde_json::to_value(target.describe(response)).unwrap();
+        assert_eq!(description["min"], "2025-01-01");
+        assert_eq!(description["max"], "2025-01-02");
+        assert_eq!(description["percentiles"], json!({"50": "2025-01-01T12:00"}));
+    }
+
+    #[test]
+    fn describe_numeric_field() {
+        let fields = [FlatField {
+            path: "status".to_string(),
+            type_: "integer".to_string(),
+            parent: None,
+            runtime: false,
+        }];
+        let target = FieldTarget::new("status", &fields).unwrap();
+        let request = target.request(10);
+        assert!(request["aggs"]["percentiles"].is_object());
+        assert_eq!(request["aggs"]["top"]["terms"]["field"], "status");
+
+        let response = |cardinality: u64| -> SearchResult {
+            serde_json::from_value(json!({
+                "hits": {"total": {"value": 10}, "hits": []},
+                "aggregations": {
+                    "missing": {"doc_count": 0},
+                    "cardinality": {"value": cardinality},
+                    "top": {"sum_other_doc_count": 0, "buckets": [{"key": 200, "doc_count": 9}]}
+                }
+            }))
+            .unwrap()
+        };
+        let description = serde_json::to_value(target.describe(response(3))).unwrap();
+        assert_eq!(description["top_values"], json!([{"value": 200, "count": 9}]));
+
+        // High cardinality numbers are measures, their top values are meaningless
+        let description = serde_json::to_value(target.describe(response(5000))).unwrap();
+        assert!(description.get("top_values").is_none());
+    }
+}
@@ -0,0 +1,632 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Tools to inspect indices: statistics for capacity planning, data streams, templates and
+//! lifecycle (ILM) status.
+
+use crate::servers::elasticsearch::base_tools::EsBaseTools;
+use crate::servers::elasticsearch::read_json;
+use elasticsearch::Elasticsearch;
+use elasticsearch::cat::CatIndicesParts;
+use elasticsearch::cluster::ClusterGetComponentTemplateParts;
+use elasticsearch::ilm::IlmExplainLifecycleParts;
+use elasticsearch::indices::{IndicesGetDataStreamParts, IndicesGetIndexTemplateParts, IndicesStatsParts};
+use rmcp::RoleServer;
+use rmcp::handler::server::tool::Parameters;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::service::RequestContext;
+use rmcp_macros::{tool, tool_router};
+use serde::{Deserialize, Serialize};
+use serde_aux::prelude::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default number of indices returned by `index_stats`
+const DEFAULT_TOP: usize = 20;
+
+const MILLIS_PER_DAY: f64 = 24.0 * 3600.0 * 1000.0;
+
+/// Number of most recent backing indices listed for a data stream
+const RECENT_BACKING_INDICES: usize = 5;
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct IndexStatsParams {
+    /// Index pattern of the indices to get statistics for (optional, default all indices)
+    index: Option<String>,
+
+    /// Statistic to sort indices by, largest first (optional, default store_size)
+    sort_by: Option<IndexStatsSort>,
+
+    /// Number of indices to return (optional, default 20)
+    top: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum IndexStatsSort {
+    /// Total size on disk, including replicas
+    #[default]
+    StoreSize,
+    /// Size of primary shards on disk
+    PrimarySize,
+    /// Number of documents
+    Docs,
+    /// Number of deleted documents not yet merged away
+    DeletedDocs,
+    /// Number of segments
+    Segments,
+    /// Number of search queries
+    Queries,
+    /// Number of indexing operations
+    Indexing,
+    /// Average growth of primary size per day since the index was created
+    Growth,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct NameParams {
+    /// Name or wildcard pattern (optional, default all)
+    name: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+struct ExplainLifecycleParams {
+    /// Name of the Elasticsearch index, data stream or index pattern
+    index: String,
+
+    /// Set to true to only list indices with a lifecycle error (optional)
+    only_errors: Option<bool>,
+}
+
+#[tool_router(router = index_tool_router, vis = "pub(super)")]
+impl EsBaseTools {
+    //---------------------------------------------------------------------------------------------
+    /// Tool: index statistics
+    #[tool(
+        description = "Get statistics of Elasticsearch indices for capacity planning: store and primary size, documents and deleted documents, segments, refresh, query and indexing totals, and growth per day since creation. Returns the largest indices first.",
+        annotations(title = "Get ES index statistics", read_only_hint = true)
+    )]
+    async fn index_stats(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(IndexStatsParams { index, sort_by, top }): Parameters<IndexStatsParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let index = index.unwrap_or_else(|| "*".to_string());
+
+        let response = es_client
+            .indices()
+            .stats(IndicesStatsParts::IndexMetric(
+                &[&index],
+                &["docs", "store", "segments", "refresh", "search", "indexing"],
+            ))
+            .send()
+            .await;
+        let stats: IndicesStatsResponse = read_json(response).await?;
+
+        // Stats don't have the index health and creation date
+        let response = es_client
+            .cat()
+            .indices(CatIndicesParts::Index(&[&index]))
+            .h(&["index", "health", "creation.date", "creation.date.string"])
+            .format("json")
+            .send()
+            .await;
+        let cat: Vec<CatIndexCreation> = read_json(response).await?;
+        let mut cat = cat.into_iter().map(|c| (c.index.clone(), c)).collect::<HashMap<_, _>>();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut indices = stats
+            .indices
+            .into_iter()
+            .map(|(name, stats)| IndexStats::new(name.clone(), stats, cat.remove(&name), now))
+            .collect::<Vec<_>>();
+
+        let sort_by = sort_by.unwrap_or_default();
+        indices.sort_by(|a, b| b.sort_key(sort_by).total_cmp(&a.sort_key(sort_by)));
+
+        let count = indices.len();
+        let top = top.unwrap_or(DEFAULT_TOP);
+        indices.truncate(top);
+
+        let totals = stats.all.total;
+        let mut summary = format!(
+            "{count} indices matching {index}: {} documents, {} in total, {} for primaries.",
+            stats.all.primaries.docs.count,
+            format_bytes(totals.store.size_in_bytes),
+            format_bytes(stats.all.primaries.store.size_in_bytes),
+        );
+        if count > indices.len() {
+            summary.push_str(&format!(" Showing the top {}.", indices.len()));
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::json(indices)?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: list data streams
+    #[tool(
+        description = "List data streams with their generation, health, index template, lifecycle policy and backing indices. Data streams are queried by their name; backing indices (named `.ds-<data stream>-<date>-<generation>`) are an implementation detail.",
+        annotations(title = "List ES data streams", read_only_hint = true)
+    )]
+    async fn list_data_streams(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(NameParams { name }): Parameters<NameParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let streams = data_streams(&es_client, name.as_deref()).await?;
+        let streams = streams.into_iter().map(DataStreamSummary::from).collect::<Vec<_>>();
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Found {} data streams:", streams.len())),
+            Content::json(streams)?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: get composable index templates
+    #[tool(
+        description = "Get composable index templates: the index patterns they apply to, the component templates they're composed of, and their settings, mappings and aliases. Without a name, only lists templates with their index patterns.",
+        annotations(title = "Get ES index templates", read_only_hint = true)
+    )]
+    async fn get_index_templates(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(NameParams { name }): Parameters<NameParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let parts = match &name {
+            Some(name) => IndicesGetIndexTemplateParts::Name(name),
+            None => IndicesGetIndexTemplateParts::None,
+        };
+        let response = es_client.indices().get_index_template(parts).send().await;
+        let response: IndexTemplatesResponse = read_json(response).await?;
+
+        let count = response.index_templates.len();
+        if name.is_some() {
+            let templates = response
+                .index_templates
+                .into_iter()
+                .map(|t| (t.name, t.index_template))
+                .collect::<BTreeMap<_, _>>();
+            return Ok(CallToolResult::success(vec![
+                Content::text(format!("Found {count} index templates:")),
+                Content::json(templates)?,
+            ]));
+        }
+
+        // Summary of each template, without the (possibly large) template body
+        let templates = response
+            .index_templates
+            .into_iter()
+            .map(|t| {
+                let summary = t
+                    .index_template
+                    .into_iter()
+                    .filter(|(key, _)| {
+                        matches!(
+                            key.as_str(),
+                            "index_patterns" | "composed_of" | "priority" | "data_stream"
+                        )
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                (t.name, summary)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "Found {count} index templates. Call again with a name to get their settings and mappings."
+            )),
+            Content::json(templates)?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: get component templates
+    #[tool(
+        description = "Get component templates, the building blocks of composable index templates, with their settings, mappings and aliases.",
+        annotations(title = "Get ES component templates", read_only_hint = true)
+    )]
+    async fn get_component_templates(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(NameParams { name }): Parameters<NameParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let names: [&str; 1];
+        let parts = match &name {
+            Some(name) => {
+                names = [name];
+                ClusterGetComponentTemplateParts::Name(&names)
+            }
+            None => ClusterGetComponentTemplateParts::None,
+        };
+        let response = es_client.cluster().get_component_template(parts).send().await;
+        let response: ComponentTemplatesResponse = read_json(response).await?;
+
+        let templates = response
+            .component_templates
+            .into_iter()
+            .map(|t| (t.name, t.component_template))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Found {} component templates:", templates.len())),
+            Content::json(templates)?,
+        ]))
+    }
+
+    //---------------------------------------------------------------------------------------------
+    /// Tool: explain index lifecycle
+    #[tool(
+        description = "Explain the index lifecycle management (ILM) status of indices: their policy, current phase, action and step, age, and any error blocking the lifecycle.",
+        annotations(title = "Explain ES index lifecycle", read_only_hint = true)
+    )]
+    async fn explain_lifecycle(
+        &self,
+        req_ctx: RequestContext<RoleServer>,
+        Parameters(ExplainLifecycleParams { index, only_errors }): Parameters<ExplainLifecycleParams>,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let es_client = self.es_client.get(req_ctx);
+        let response = es_client
+            .ilm()
+            .explain_lifecycle(IlmExplainLifecycleParts::Index(&index))
+            .only_errors(only_errors.unwrap_or(false))
+            .send()
+            .await;
+        let response: IlmExplainResponse = read_json(response).await?;
+
+        let mut indices = response.indices.into_iter().collect::<Vec<_>>();
+        indices.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+        let managed = indices.iter().filter(|(_, i)| i.managed).count();
+        let failed = indices.iter().filter(|(_, i)| i.failed_step.is_some()).count();
+
+        let mut summary = format!("{} indices, {managed} managed by ILM.", indices.len());
+        if failed > 0 {
+            summary.push_str(&format!(
+                " {failed} indices have a failed lifecycle step (see 'failed_step' and 'step_info')."
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::json(indices.into_iter().collect::<BTreeMap<_, _>>())?,
+        ]))
+    }
+}
+
+/// Get data streams matching a name or pattern.
+pub(super) async fn data_streams(
+    es_client: &Elasticsearch,
+    name: Option<&str>,
+) -> Result<Vec<DataStream>, rmcp::Error> {
+    let names: [&str; 1];
+    let parts = match name {
+        Some(name) => {
+            names = [name];
+            IndicesGetDataStreamParts::Name(&names)
+        }
+        None => IndicesGetDataStreamParts::None,
+    };
+    let response = es_client.indices().get_data_stream(parts).send().await;
+    let response: DataStreamsResponse = read_json(response).await?;
+    Ok(response.data_streams)
+}
+
+//----- Responses
+
+#[derive(Deserialize)]
+struct IndicesStatsResponse {
+    #[serde(rename = "_all")]
+    all: IndexStatsGroups,
+    #[serde(default)]
+    indices: HashMap<String, IndexStatsGroups>,
+}
+
+#[derive(Deserialize)]
+struct IndexStatsGroups {
+    primaries: StatsMetrics,
+    total: StatsMetrics,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct StatsMetrics {
+    docs: DocsStats,
+    store: StoreStats,
+    segments: SegmentsStats,
+    refresh: RefreshStats,
+    search: SearchStats,
+    indexing: IndexingStats,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct DocsStats {
+    count: u64,
+    deleted: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct StoreStats {
+    size_in_bytes: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SegmentsStats {
+    count: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RefreshStats {
+    total: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SearchStats {
+    query_total: u64,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct IndexingStats {
+    index_total: u64,
+}
+
+#[derive(Deserialize)]
+struct CatIndexCreation {
+    index: String,
+    health: Option<String>,
+    #[serde(rename = "creation.date", deserialize_with = "deserialize_option_number_from_string")]
+    creation_date: Option<u64>,
+    #[serde(rename = "creation.date.string")]
+    creation_date_string: Option<String>,
+}
+
+/// Statistics of an index, as returned by the tool
+#[derive(Serialize)]
+struct IndexStats {
+    index: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<String>,
+    docs: u64,
+    deleted_docs: u64,
+    store_size: String,
+    primary_size: String,
+    segments: u64,
+    refreshes: u64,
+    queries: u64,
+    indexing_ops: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    /// Primary size divided by the age of the index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    growth_per_day: Option<String>,
+    #[serde(skip)]
+    store_bytes: u64,
+    #[serde(skip)]
+    primary_bytes: u64,
+    #[serde(skip)]
+    growth_bytes: f64,
+}
+
+impl IndexStats {
+    fn new(index: String, stats: IndexStatsGroups, cat: Option<CatIndexCreation>, now: u64) -> Self {
+        let (health, creation_date, created) = match cat {
+            Some(cat) => (cat.health, cat.creation_date, cat.creation_date_string),
+            None => (None, None, None),
+        };
+
+        let primary_bytes = stats.primaries.store.size_in_bytes;
+        let growth = creation_date
+            .filter(|date| *date < now)
+            .map(|date| primary_bytes as f64 / ((now - date) as f64 / MILLIS_PER_DAY).max(1.0));
+
+        IndexStats {
+            index,
+            health,
+            docs: stats.primaries.docs.count,
+            deleted_docs: stats.primaries.docs.deleted,
+            store_size: format_bytes(stats.total.store.size_in_bytes),
+            primary_size: format_bytes(primary_bytes),
+            segments: stats.total.segments.count,
+            refreshes: stats.total.refresh.total,
+            queries: stats.total.search.query_total,
+            indexing_ops: stats.primaries.indexing.index_total,
+            created,
+            growth_per_day: growth.map(|g| format_bytes(g as u64)),
+            store_bytes: stats.total.store.size_in_bytes,
+            primary_bytes,
+            growth_bytes: growth.unwrap_or_default(),
+        }
+    }
+
+    fn sort_key(&self, sort: IndexStatsSort) -> f64 {
+        match sort {
+            IndexStatsSort::StoreSize => self.store_bytes as f64,
+            IndexStatsSort::PrimarySize => self.primary_bytes as f64,
+            IndexStatsSort::Docs => self.docs as f64,
+            IndexStatsSort::DeletedDocs => self.deleted_docs as f64,
+            IndexStatsSort::Segments => self.segments as f64,
+            IndexStatsSort::Queries => self.queries as f64,
+            IndexStatsSort::Indexing => self.indexing_ops as f64,
+            IndexStatsSort::Growth => self.growth_bytes,
+        }
+    }
+}
+
+//----- Data streams, templates and lifecycle
+
+#[derive(Deserialize)]
+struct DataStreamsResponse {
+    data_streams: Vec<DataStream>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct DataStream {
+    pub name: String,
+    pub generation: u64,
+    /// Health of the backing indices (GREEN, YELLOW or RED)
+    pub status: Option<String>,
+    pub template: Option<String>,
+    pub ilm_policy: Option<String>,
+    /// Data stream lifecycle, an alternative to ILM
+    pub lifecycle: Option<Value>,
+    /// Backing indices, oldest first. The last one is the write index.
+    pub indices: Vec<BackingIndex>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct BackingIndex {
+    pub index_name: String,
+}
+
+/// A data stream, with only its most recent backing indices
+#[derive(Serialize)]
+struct DataStreamSummary {
+    name: String,
+    generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ilm_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lifecycle: Option<Value>,
+    backing_indices: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    write_index: Option<String>,
+    /// Most recent backing indices, oldest first
+    recent_backing_indices: Vec<String>,
+}
+
+impl From<DataStream> for DataStreamSummary {
+    fn from(stream: DataStream) -> Self {
+        let count = stream.indices.len();
+        let recent = stream
+            .indices
+            .into_iter()
+            .skip(count.saturating_sub(RECENT_BACKING_INDICES))
+            .map(|i| i.index_name)
+            .collect::<Vec<_>>();
+
+        DataStreamSummary {
+            name: stream.name,
+            generation: stream.generation,
+            health: stream.status,
+            template: stream.template,
+            ilm_policy: stream.ilm_policy,
+            lifecycle: stream.lifecycle,
+            backing_indices: count,
+            write_index: recent.last().cloned(),
+            recent_backing_indices: recent,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IndexTemplatesResponse {
+    index_templates: Vec<NamedIndexTemplate>,
+}
+
+#[derive(Deserialize)]
+struct NamedIndexTemplate {
+    name: String,
+    index_template: serde_json::Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ComponentTemplatesResponse {
+    component_templates: Vec<NamedComponentTemplate>,
+}
+
+#[derive(Deserialize)]
+struct NamedComponentTemplate {
+    name: String,
+    component_template: Value,
+}
+
+#[derive(Deserialize)]
+struct IlmExplainResponse {
+    indices: HashMap<String, IlmIndex>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IlmIndex {
+    managed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    age: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step: Option<String>,
+    /// Step that failed, when `step` is `ERROR`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed_step: Option<String>,
+    /// Details of the current step, including errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_info: Option<Value>,
+}
+
+/// Format a size in bytes with the same units as the cat APIs, e.g. `1.5gb`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["kb", "mb", "gb", "tb", "pb"];
+    if bytes < 1024 {
+        return format!("{bytes}b");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "b";
+    for u in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = u;
+    }
+    format!("{size:.1}{unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_format() {
+        assert_eq!(format_bytes(512), "512b");
+        assert_eq!(format_bytes(1536), "1.5kb");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.0gb");
+    }
+}
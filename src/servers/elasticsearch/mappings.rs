@@ -0,0 +1,285 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Index mappings, and their flattened representation as a catalog of dotted field paths.
+
+use indexmap::IndexMap;
+use rmcp::model::JsonObject;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+//----- Mappings
+
+/// Response of the get mapping API, by index name
+pub type MappingResponse = HashMap<String, Mappings>;
+
+#[derive(Serialize, Deserialize)]
+pub struct Mappings {
+    pub mappings: Mapping,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Mapping {
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<JsonObject>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub properties: IndexMap<String, MappingProperty>,
+    /// Fields evaluated at query time
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub runtime: IndexMap<String, RuntimeField>,
+    /// Templates applied to new fields, as a list of single-entry objects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dynamic_templates: Vec<IndexMap<String, Value>>,
+    /// Other settings (`dynamic`, `_source`, `_routing`, etc.)
+    #[serde(flatten)]
+    pub settings: IndexMap<String, Value>,
+}
+
+/// A field. Object fields have `properties` and no `type`.
+#[derive(Serialize, Deserialize)]
+pub struct MappingProperty {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    /// Sub-fields of `object` and `nested` fields
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub properties: IndexMap<String, MappingProperty>,
+    /// Multi-fields, i.e. the same value indexed differently (e.g. `message.keyword`)
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: IndexMap<String, MappingProperty>,
+    #[serde(flatten)]
+    pub settings: IndexMap<String, Value>,
+}
+
+impl MappingProperty {
+    pub fn type_name(&self) -> &str {
+        self.type_.as_deref().unwrap_or("object")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RuntimeField {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(flatten)]
+    pub settings: IndexMap<String, Value>,
+}
+
+/// A field with its dotted path
+#[derive(Debug, PartialEq)]
+pub struct FlatField {
+    pub path: String,
+    pub type_: String,
+    /// For multi-fields, the path of the field they're a variant of
+    pub parent: Option<String>,
+    pub runtime: bool,
+}
+
+impl Mapping {
+    /// All fields with their dotted paths, including objects, multi-fields and runtime fields
+    pub fn flatten(&self) -> Vec<FlatField> {
+        let mut fields = Vec::new();
+        flatten_properties("", &self.properties, &mut fields);
+        for (path, field) in &self.runtime {
+            fields.push(FlatField {
+                path: path.clone(),
+                type_: field.type_.clone(),
+                parent: None,
+                runtime: true,
+            });
+        }
+        fields
+    }
+}
+
+fn flatten_properties(prefix: &str, properties: &IndexMap<String, MappingProperty>, fields: &mut Vec<FlatField>) {
+    for (name, property) in properties {
+        let path = format!("{prefix}{name}");
+        fields.push(FlatField {
+            path: path.clone(),
+            type_: property.type_name().to_string(),
+            parent: None,
+            runtime: false,
+        });
+        for (sub_name, sub_field) in &property.fields {
+            fields.push(FlatField {
+                path: format!("{path}.{sub_name}"),
+                type_: sub_field.type_name().to_string(),
+                parent: Some(path.clone()),
+                runtime: false,
+            });
+        }
+        flatten_properties(&format!("{path}."), &property.properties, fields);
+    }
+}
+
+//----- Field catalog
+
+/// Fields of one or more indices, merged by dotted path.
+#[derive(Serialize)]
+pub struct FieldCatalog {
+    pub indices: Vec<String>,
+    pub fields: BTreeMap<String, CatalogField>,
+}
+
+#[derive(Serialize)]
+pub struct CatalogField {
+    /// Field type, or `conflict` if it differs between indices
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Indices having this field, if not all of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indices: Option<Vec<String>>,
+    /// Indices for each type, for conflicting fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<BTreeMap<String, Vec<String>>>,
+    /// For multi-fields, the field they're a variant of (e.g. `message` for `message.keyword`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_field_of: Option<String>,
+    /// Is this a runtime field?
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub runtime: bool,
+}
+
+impl FieldCatalog {
+    pub fn new(response: &MappingResponse) -> Self {
+        let mut indices = response.keys().cloned().collect::<Vec<_>>();
+        indices.sort();
+
+        // path -> type -> indices
+        let mut by_path: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        let mut parents: HashMap<String, String> = HashMap::new();
+        let mut runtime: BTreeSet<String> = BTreeSet::new();
+        for index in &indices {
+            for field in response[index].mappings.flatten() {
+                if let Some(parent) = field.parent {
+                    parents.insert(field.path.clone(), parent);
+                }
+                if field.runtime {
+                    runtime.insert(field.path.clone());
+                }
+                by_path
+                    .entry(field.path)
+                    .or_default()
+                    .entry(field.type_)
+                    .or_default()
+                    .push(index.clone());
+            }
+        }
+
+        let fields = by_path
+            .into_iter()
+            .map(|(path, types)| {
+                let field_indices = types.values().flatten().cloned().collect::<BTreeSet<_>>();
+                let partial = (field_indices.len() < indices.len()).then(|| field_indices.into_iter().collect());
+                let (type_, types) = if types.len() == 1 {
+                    (types.into_keys().next().unwrap(), None)
+                } else {
+                    ("conflict".to_string(), Some(types))
+                };
+                let field = CatalogField {
+                    type_,
+                    indices: partial,
+                    types,
+                    multi_field_of: parents.remove(&path),
+                    runtime: runtime.contains(&path),
+                };
+                (path, field)
+            })
+            .collect();
+
+        FieldCatalog { indices, fields }
+    }
+
+    pub fn conflicts(&self) -> usize {
+        self.fields.values().filter(|f| f.types.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn recursive_mapping() {
+        let mapping = json!({
+            "dynamic": "strict",
+            "dynamic_templates": [
+                {"strings_as_keyword": {"match_mapping_type": "string", "mapping": {"type": "keyword"}}}
+            ],
+            "properties": {
+                "message": {"type": "text", "fields": {"keyword": {"type": "keyword", "ignore_above": 256}}},
+                "host": {"properties": {"name": {"type": "keyword"}}},
+                "events": {"type": "nested", "properties": {"code": {"type": "integer"}}}
+            },
+            "runtime": {
+                "day_of_week": {"type": "keyword", "script": {"source": "emit('Monday')"}}
+            }
+        });
+        let parsed: Mapping = serde_json::from_value(mapping.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), mapping);
+
+        let paths = parsed
+            .flatten()
+            .into_iter()
+            .map(|f| (f.path, f.type_))
+            .collect::<Vec<_>>();
+        let paths = paths.iter().map(|(p, t)| (p.as_str(), t.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                ("events", "nested"),
+                ("events.code", "integer"),
+                ("host", "object"),
+                ("host.name", "keyword"),
+                ("message", "text"),
+                ("message.keyword", "keyword"),
+                ("day_of_week", "keyword"),
+            ]
+        );
+    }
+
+    #[test]
+    fn field_catalog() {
+        let response: MappingResponse = serde_json::from_value(json!({
+            "logs-1": {"mappings": {"properties": {
+                "message": {"type": "text", "fields": {"keyword": {"type": "keyword"}}},
+                "status": {"type": "keyword"}
+            }}},
+            "logs-2": {"mappings": {"properties": {
+                "message": {"type": "text"},
+                "status": {"type": "long"},
+                "host": {"type": "object", "properties": {"name": {"type": "keyword"}}}
+            }}}
+        }))
+        .unwrap();
+
+        let catalog = FieldCatalog::new(&response);
+        assert_eq!(catalog.conflicts(), 1);
+        assert_eq!(
+            serde_json::to_value(&catalog.fields).unwrap(),
+            json!({
+                "host": {"type": "object", "indices": ["logs-2"]},
+                "host.name": {"type": "keyword", "indices": ["logs-2"]},
+                "message": {"type": "text"},
+                "message.keyword": {"type": "keyword", "indices": ["logs-1"], "multi_field_of": "message"},
+                "status": {"type": "conflict", "types": {"keyword": ["logs-1"], "long": ["logs-2"]}}
+            })
+        );
+    }
+}
@@ -15,7 +15,20 @@
 // specific language governing permissions and limitations
 // under the License.
 
+mod async_tools;
 mod base_tools;
+mod cluster_tools;
+mod completion;
+mod custom_tools;
+mod document_tools;
+mod errors;
+mod field_tools;
+mod index_tools;
+mod mappings;
+mod pagination;
+mod prompts;
+mod resources;
+mod suggestions;
 
 use crate::servers::IncludeExclude;
 use crate::utils::none_if_empty_string;
@@ -29,13 +42,16 @@ use http::request::Parts;
 use http::{HeaderValue, header};
 use indexmap::IndexMap;
 use rmcp::RoleServer;
-use rmcp::model::ToolAnnotations;
+use rmcp::handler::server::tool::ToolRoute;
+use rmcp::model::{Content, ToolAnnotations};
 use rmcp::service::RequestContext;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_aux::field_attributes::deserialize_bool_from_anything;
+use serde_json::Value;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ElasticsearchMcpConfig {
@@ -62,20 +78,19 @@ pub struct ElasticsearchMcpConfig {
     #[serde(default)]
     pub tools: Tools,
 
-    /// Prompts
+    /// Prompts, by name
     #[serde(default)]
-    pub prompts: Vec<String>,
-    // TODO: search as resources?
+    pub prompts: IndexMap<String, PromptConfig>,
 }
 
 // A wrapper around an ES client that provides a client instance configured
 /// for a given request context (i.e. auth credentials)
 #[derive(Clone)]
-pub struct EsClientProvider(Elasticsearch);
+pub struct EsClientProvider(Elasticsearch, RandomState);
 
 impl EsClientProvider {
     pub fn new(client: Elasticsearch) -> Self {
-        EsClientProvider(client)
+        EsClientProvider(client, RandomState::new())
     }
 
     /// If the incoming request is a http request and has an `Authorization` header, use it
@@ -83,12 +98,7 @@ impl EsClientProvider {
     pub fn get(&self, context: RequestContext<RoleServer>) -> Cow<'_, Elasticsearch> {
         let client = &self.0;
 
-        let Some(mut auth) = context
-            .extensions
-            .get::<Parts>()
-            .and_then(|p| p.headers.get(header::AUTHORIZATION))
-            .and_then(|h| h.to_str().ok())
-        else {
+        let Some(mut auth) = Self::auth_header(&context) else {
             // No auth
             return Cow::Borrowed(client);
         };
@@ -104,12 +114,27 @@ impl EsClientProvider {
 
         Cow::Owned(Elasticsearch::new(transport))
     }
+
+    /// Identifies the credentials used for a request, to cache data that depends on access rights.
+    /// This is a hash of the credentials, so that they aren't kept in memory.
+    pub fn credentials_key(&self, context: &RequestContext<RoleServer>) -> u64 {
+        self.1.hash_one(Self::auth_header(context).unwrap_or_default())
+    }
+
+    fn auth_header(context: &RequestContext<RoleServer>) -> Option<&str> {
+        context
+            .extensions
+            .get::<Parts>()
+            .and_then(|p| p.headers.get(header::AUTHORIZATION))
+            .and_then(|h| h.to_str().ok())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Tools {
     #[serde(flatten)]
     pub incl_excl: Option<IncludeExclude>,
+    #[serde(default)]
     pub custom: HashMap<String, CustomTool>,
 }
 
@@ -145,21 +170,61 @@ pub struct EsqlTool {
     format: EsqlResultFormat,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, schemars::JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EsqlResultFormat {
     #[default]
-    // Output as JSON, either as an array of objects or as a single object.
+    /// Output as JSON, as an array of objects
     Json,
-    // If a single object with a single property, output only its value
+    /// If a single object with a single property, output only its value
     Value,
-    //Csv,
+    /// Comma-separated values, with a header row
+    Csv,
+    /// Tab-separated values, with a header row
+    Tsv,
+    /// Markdown table
+    Markdown,
+}
+
+impl EsqlResultFormat {
+    /// Format an ES|QL response as tool result content.
+    pub fn format(&self, response: base_tools::EsqlQueryResponse) -> Result<Vec<Content>, rmcp::Error> {
+        // Tabular formats avoid repeating column names on every row
+        let table = match self {
+            EsqlResultFormat::Csv => Some(response.to_delimited(',')),
+            EsqlResultFormat::Tsv => Some(response.to_delimited('\t')),
+            EsqlResultFormat::Markdown => Some(response.to_markdown()),
+            _ => None,
+        };
+        if let Some(table) = table {
+            return Ok(vec![Content::text(response.column_types()), Content::text(table)]);
+        }
+
+        let objects = response.into_objects();
+
+        if let EsqlResultFormat::Value = self
+            && let [Value::Object(obj)] = objects.as_slice()
+            && obj.len() == 1
+        {
+            let value = obj.values().next().unwrap();
+            return Ok(vec![match value {
+                Value::String(s) => Content::text(s),
+                _ => Content::json(value)?,
+            }]);
+        }
+
+        // Always an array, even for a single row, so that the output shape doesn't depend on the data
+        Ok(vec![Content::json(objects)?])
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchTemplateTool {
     #[serde(flatten)]
     base: ToolBase,
+    /// Index or index pattern to search. Defaults to all indices.
+    #[serde(default)]
+    index: Option<String>,
     #[serde(flatten)]
     template: SearchTemplate,
 }
@@ -171,11 +236,59 @@ pub enum SearchTemplate {
     Template(serde_json::Value), // or constrain to an object?
 }
 
+/// A prompt template
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptConfig {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: IndexMap<String, PromptArgumentConfig>,
+    /// Prompt text. Argument values are inserted using `{{name}}` placeholders.
+    pub template: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptArgumentConfig {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, rename = "type")]
+    pub type_: PromptArgumentType,
+    /// Value used if the argument is not provided
+    pub default: Option<String>,
+    /// Values suggested to clients. Arguments named `index` are completed with index names by default.
+    pub completion: Option<ArgumentCompletion>,
+}
+
+/// Source of argument completion values
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArgumentCompletion {
+    /// Index, alias and data stream names
+    Index,
+    /// Field names of the indices matching a pattern (all indices by default)
+    Field { index: Option<String> },
+}
+
+/// Prompt arguments are always strings in MCP, the type is used to validate their value.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptArgumentType {
+    #[default]
+    String,
+    Number,
+    Integer,
+    Boolean,
+}
+
 #[derive(Clone)]
 pub struct ElasticsearchMcp {}
 
 impl ElasticsearchMcp {
-    pub fn new_with_config(config: ElasticsearchMcpConfig, container_mode: bool) -> anyhow::Result<base_tools::EsBaseTools> {
+    pub fn new_with_config(
+        config: ElasticsearchMcpConfig,
+        container_mode: bool,
+        extra_routes: Vec<ToolRoute<base_tools::EsBaseTools>>,
+    ) -> anyhow::Result<base_tools::EsBaseTools> {
         let creds = if let Some(api_key) = config.api_key.clone() {
             Some(Credentials::EncodedApiKey(api_key))
         } else if let Some(username) = config.username.clone() {
@@ -210,7 +323,12 @@ impl ElasticsearchMcp {
         let transport = transport.build()?;
         let es_client = Elasticsearch::new(transport);
 
-        Ok(base_tools::EsBaseTools::new(es_client))
+        Ok(base_tools::EsBaseTools::new(
+            es_client,
+            config.tools,
+            config.prompts,
+            extra_routes,
+        ))
     }
 }
 
@@ -247,20 +365,23 @@ pub fn internal_error(e: impl std::error::Error) -> rmcp::Error {
 }
 
 /// Return an error as an error response to the client, which may be able to take
-/// action to correct it. This should be refined to handle common error types such
-/// as index not found, which could be caused by the client hallucinating an index name.
-///
-/// TODO (in rmcp): if rmcp::Error had a variant that accepts a CallToolResult, this would
-/// allow to use the '?' operator while sending a result to the client.
-pub fn handle_error(result: Result<Response, elasticsearch::Error>) -> Result<Response, rmcp::Error> {
-    match result {
-        Ok(resp) => resp.error_for_status_code(),
-        Err(e) => {
-            tracing::error!("Error: {:?}", &e);
-            Err(e)
-        }
+/// action to correct it. Error responses from Elasticsearch are parsed to provide details
+/// like the reason and location of the error (see [`errors`]).
+pub async fn handle_error(result: Result<Response, elasticsearch::Error>) -> Result<Response, rmcp::Error> {
+    let response = result.map_err(|e| {
+        tracing::error!("Error: {:?}", &e);
+        internal_error(e)
+    })?;
+
+    let status = response.status_code();
+    if status.is_client_error() || status.is_server_error() {
+        let body = response.text().await.unwrap_or_default();
+        let error = errors::ElasticsearchError::parse(status.as_u16(), &body);
+        tracing::debug!("Elasticsearch error: {:?}", &error);
+        return Err(error.into_rmcp_error());
     }
-    .map_err(internal_error)
+
+    Ok(response)
 }
 
 pub async fn read_json<T: DeserializeOwned>(
@@ -270,12 +391,12 @@ pub async fn read_json<T: DeserializeOwned>(
     // tracing::debug!("Received json {text}");
     // serde_json::from_str(&text).map_err(internal_error)
 
-    let response = handle_error(response)?;
+    let response = handle_error(response).await?;
     response.json().await.map_err(internal_error)
 }
 
 #[allow(dead_code)]
 pub async fn read_text(result: Result<Response, elasticsearch::Error>) -> Result<String, rmcp::Error> {
-    let response = handle_error(result)?;
+    let response = handle_error(result).await?;
     response.text().await.map_err(internal_error)
 }
@@ -0,0 +1,163 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Search pagination with `search_after` over a point in time (PIT).
+//!
+//! The state needed to get the next page (PIT id, original query and sort values of the last hit)
+//! is sent to the client as an opaque cursor.
+
+use crate::servers::elasticsearch::base_tools::SearchResult;
+use crate::servers::elasticsearch::{handle_error, read_json};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use elasticsearch::{Elasticsearch, OpenPointInTimeParts, SearchParts};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// How long a PIT is kept between two pages
+const KEEP_ALIVE: &str = "5m";
+
+/// Default number of hits in a search response
+const DEFAULT_SIZE: u64 = 10;
+
+#[derive(Serialize, Deserialize)]
+struct SearchCursor {
+    pit_id: String,
+    /// The original query, without `from` and aggregations that only apply to the first page
+    query: Map<String, Value>,
+    search_after: Vec<Value>,
+}
+
+impl SearchCursor {
+    fn encode(&self) -> String {
+        // Serializing a struct of strings and json values cannot fail
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Result<Self, rmcp::Error> {
+        URL_SAFE_NO_PAD
+            .decode(cursor.trim())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| rmcp::Error::invalid_params("invalid cursor", None))
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenPointInTimeResponse {
+    id: String,
+}
+
+/// Run the first page of a paginated search. Returns the results and a cursor if there are
+/// more pages.
+pub async fn first_page(
+    es_client: &Elasticsearch,
+    index: &str,
+    query: Map<String, Value>,
+) -> Result<(SearchResult, Option<String>), rmcp::Error> {
+    let response = es_client
+        .open_point_in_time(OpenPointInTimeParts::Index(&[index]))
+        .keep_alive(KEEP_ALIVE)
+        .send()
+        .await;
+    let pit: OpenPointInTimeResponse = read_json(response).await?;
+
+    let result = search_page(es_client, pit.id.clone(), query, None).await;
+    if result.is_err() {
+        // Nobody will get a cursor for this PIT: release it rather than waiting for it to expire
+        close_pit(es_client, &pit.id).await;
+    }
+    result
+}
+
+/// Get the page following a cursor. Returns the results and a cursor if there are more pages.
+pub async fn next_page(es_client: &Elasticsearch, cursor: &str) -> Result<(SearchResult, Option<String>), rmcp::Error> {
+    let cursor = SearchCursor::decode(cursor)?;
+    search_page(es_client, cursor.pit_id, cursor.query, Some(cursor.search_after)).await
+}
+
+async fn search_page(
+    es_client: &Elasticsearch,
+    pit_id: String,
+    mut query: Map<String, Value>,
+    search_after: Option<Vec<Value>>,
+) -> Result<(SearchResult, Option<String>), rmcp::Error> {
+    let mut body = query.clone();
+    body.insert("pit".to_string(), json!({ "id": pit_id, "keep_alive": KEEP_ALIVE }));
+    if let Some(search_after) = search_after {
+        body.insert("search_after".to_string(), json!(search_after));
+    }
+
+    // Searches on a PIT must not target an index
+    let response = es_client.search(SearchParts::None).body(body).send().await;
+    let response: SearchResult = read_json(response).await?;
+
+    // The PIT id may change between pages
+    let pit_id = response.pit_id.clone().unwrap_or(pit_id);
+    let size = query.get("size").and_then(Value::as_u64).unwrap_or(DEFAULT_SIZE);
+    let last_sort = response.hits.hits.last().and_then(|hit| hit.sort.clone());
+
+    match last_sort {
+        Some(search_after) if size > 0 && response.hits.hits.len() as u64 >= size => {
+            for key in ["from", "aggs", "aggregations"] {
+                query.remove(key);
+            }
+            let cursor = SearchCursor {
+                pit_id,
+                query,
+                search_after,
+            };
+            Ok((response, Some(cursor.encode())))
+        }
+        _ => {
+            // Exhausted: release the PIT now rather than waiting for it to expire
+            close_pit(es_client, &pit_id).await;
+            Ok((response, None))
+        }
+    }
+}
+
+async fn close_pit(es_client: &Elasticsearch, pit_id: &str) {
+    let response = es_client
+        .close_point_in_time()
+        .body(json!({ "id": pit_id }))
+        .send()
+        .await;
+    if let Err(e) = handle_error(response).await {
+        tracing::warn!("Failed to close point in time: {}", e.message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_roundtrip() {
+        let cursor = SearchCursor {
+            pit_id: "pit-1".to_string(),
+            query: json!({"query": {"match_all": {}}}).as_object().unwrap().clone(),
+            search_after: vec![json!(1.5), json!(42)],
+        };
+        let decoded = SearchCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.pit_id, "pit-1");
+        assert_eq!(decoded.query, cursor.query);
+        assert_eq!(decoded.search_after, cursor.search_after);
+
+        assert!(SearchCursor::decode("not a cursor").is_err());
+    }
+}
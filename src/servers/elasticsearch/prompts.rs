@@ -0,0 +1,198 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Prompts defined in the `prompts` section of the configuration file.
+
+use crate::servers::elasticsearch::{ArgumentCompletion, PromptArgumentType, PromptConfig};
+use indexmap::IndexMap;
+use rmcp::model::{GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+use serde_json::Value;
+
+/// Prompt definitions, in configuration order.
+pub struct Prompts(IndexMap<String, PromptConfig>);
+
+impl Prompts {
+    pub fn new(prompts: IndexMap<String, PromptConfig>) -> Self {
+        Prompts(prompts)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Prompt descriptions for `prompts/list`
+    pub fn list(&self) -> Vec<Prompt> {
+        self.0
+            .iter()
+            .map(|(name, prompt)| {
+                let arguments = prompt
+                    .arguments
+                    .iter()
+                    .map(|(name, arg)| PromptArgument {
+                        name: name.clone(),
+                        description: arg.description.clone(),
+                        required: Some(arg.required),
+                    })
+                    .collect::<Vec<_>>();
+
+                Prompt::new(name, prompt.description.clone(), Some(arguments))
+            })
+            .collect()
+    }
+
+    /// Completion values for a prompt argument, if any
+    pub fn completion(&self, name: &str, argument: &str) -> Option<ArgumentCompletion> {
+        let arg = self.0.get(name)?.arguments.get(argument)?;
+        match &arg.completion {
+            Some(completion) => Some(completion.clone()),
+            None if argument == "index" => Some(ArgumentCompletion::Index),
+            None => None,
+        }
+    }
+
+    /// Render a prompt for `prompts/get`
+    pub fn get(&self, name: &str, args: Option<JsonObject>) -> Result<GetPromptResult, rmcp::Error> {
+        let prompt = self
+            .0
+            .get(name)
+            .ok_or_else(|| rmcp::Error::invalid_params(format!("prompt '{name}' not found"), None))?;
+
+        let args = args.unwrap_or_default();
+        let mut values = Vec::new();
+        for (arg_name, arg) in &prompt.arguments {
+            let value = match args.get(arg_name) {
+                None | Some(Value::Null) => {
+                    if arg.required {
+                        return Err(rmcp::Error::invalid_params(
+                            format!("missing argument '{arg_name}'"),
+                            None,
+                        ));
+                    }
+                    arg.default.clone().unwrap_or_default()
+                }
+                Some(Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+            };
+
+            if !value.is_empty() && !arg.type_.is_valid(&value) {
+                return Err(rmcp::Error::invalid_params(
+                    format!(
+                        "argument '{arg_name}' should be {}, got '{value}'",
+                        arg.type_.article_name()
+                    ),
+                    None,
+                ));
+            }
+            values.push((arg_name.as_str(), value));
+        }
+
+        Ok(GetPromptResult {
+            description: prompt.description.clone(),
+            messages: vec![PromptMessage::new_text(
+                PromptMessageRole::User,
+                render(&prompt.template, &values),
+            )],
+        })
+    }
+}
+
+impl PromptArgumentType {
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            PromptArgumentType::String => true,
+            PromptArgumentType::Number => value.parse::<f64>().is_ok(),
+            PromptArgumentType::Integer => value.parse::<i64>().is_ok(),
+            PromptArgumentType::Boolean => value == "true" || value == "false",
+        }
+    }
+
+    fn article_name(&self) -> &'static str {
+        match self {
+            PromptArgumentType::String => "a string",
+            PromptArgumentType::Number => "a number",
+            PromptArgumentType::Integer => "an integer",
+            PromptArgumentType::Boolean => "a boolean",
+        }
+    }
+}
+
+/// Replace `{{name}}` placeholders (whitespace inside braces is allowed) with argument values.
+/// Unknown placeholders are left untouched.
+fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = after[..end].trim();
+        match values.iter().find(|(n, _)| *n == name) {
+            Some((_, value)) => result.push_str(value),
+            None => result.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prompts() -> Prompts {
+        let config = json!({
+            "error-spike": {
+                "description": "Investigate an error spike",
+                "arguments": {
+                    "index": { "description": "Index to look at", "required": true },
+                    "hours": { "type": "integer", "default": "24" }
+                },
+                "template": "Find errors in {{ index }} over the last {{hours}} hours. Keep {{unknown}}."
+            }
+        });
+        Prompts::new(serde_json::from_value(config).unwrap())
+    }
+
+    #[test]
+    fn render_prompt() {
+        let prompts = prompts();
+        let args = json!({"index": "logs-*"}).as_object().cloned();
+        let result = prompts.get("error-spike", args).unwrap();
+        let PromptMessage { content, .. } = &result.messages[0];
+        assert_eq!(
+            content,
+            &rmcp::model::PromptMessageContent::text("Find errors in logs-* over the last 24 hours. Keep {{unknown}}.")
+        );
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let prompts = prompts();
+        assert!(prompts.get("error-spike", None).is_err());
+        let args = json!({"index": "logs-*", "hours": "many"}).as_object().cloned();
+        assert!(prompts.get("error-spike", args).is_err());
+        assert!(prompts.get("not-a-prompt", None).is_err());
+    }
+}
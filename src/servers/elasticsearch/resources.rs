@@ -0,0 +1,200 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Index metadata exposed as MCP resources, so that clients can attach it to the context
+//! without the model having to call tools.
+
+use crate::servers::elasticsearch::base_tools::{CatIndexResponse, SearchResult};
+use crate::servers::elasticsearch::{ArgumentCompletion, internal_error, read_json};
+use elasticsearch::cat::CatIndicesParts;
+use elasticsearch::indices::{IndicesGetMappingParts, IndicesGetSettingsParts};
+use elasticsearch::{Elasticsearch, SearchParts};
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, ReadResourceResult, Resource, ResourceContents, ResourceTemplate,
+};
+use serde_json::{Value, json};
+
+const URI_PREFIX: &str = "elasticsearch://index/";
+
+/// Number of documents returned by the `sample` resource
+const SAMPLE_SIZE: usize = 5;
+
+/// The kinds of resources available for an index
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum IndexResource {
+    Mapping,
+    Settings,
+    Sample,
+}
+
+impl IndexResource {
+    const ALL: [IndexResource; 3] = [IndexResource::Mapping, IndexResource::Settings, IndexResource::Sample];
+
+    fn name(&self) -> &'static str {
+        match self {
+            IndexResource::Mapping => "mapping",
+            IndexResource::Settings => "settings",
+            IndexResource::Sample => "sample",
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            IndexResource::Mapping => "Field mappings of an Elasticsearch index".to_string(),
+            IndexResource::Settings => "Settings of an Elasticsearch index".to_string(),
+            IndexResource::Sample => format!("A sample of {SAMPLE_SIZE} documents from an Elasticsearch index"),
+        }
+    }
+
+    fn uri(&self, index: &str) -> String {
+        format!("{URI_PREFIX}{index}/{}", self.name())
+    }
+}
+
+/// Parse a resource URI into an index name and resource kind.
+fn parse_uri(uri: &str) -> Option<(&str, IndexResource)> {
+    let (index, kind) = uri.strip_prefix(URI_PREFIX)?.rsplit_once('/')?;
+    if index.is_empty() {
+        return None;
+    }
+    let kind = IndexResource::ALL.into_iter().find(|r| r.name() == kind)?;
+    Some((index, kind))
+}
+
+/// Resource templates for `resources/templates/list`
+pub fn templates() -> Vec<ResourceTemplate> {
+    IndexResource::ALL
+        .iter()
+        .map(|r| {
+            RawResourceTemplate {
+                uri_template: r.uri("{name}"),
+                name: format!("Index {}", r.name()),
+                description: Some(r.description()),
+                mime_type: Some("application/json".to_string()),
+            }
+            .no_annotation()
+        })
+        .collect()
+}
+
+/// Completion values for a resource template argument, if any
+pub fn completion(uri_template: &str, argument: &str) -> Option<ArgumentCompletion> {
+    (uri_template.starts_with(URI_PREFIX) && argument == "name").then_some(ArgumentCompletion::Index)
+}
+
+/// Index mappings for `resources/list`. Settings and samples are only available through templates
+/// to keep the list short on clusters with many indices.
+pub async fn list(es_client: &Elasticsearch) -> Result<Vec<Resource>, rmcp::Error> {
+    let response = es_client
+        .cat()
+        .indices(CatIndicesParts::None)
+        .h(&["index", "status", "docs.count"])
+        .format("json")
+        .send()
+        .await;
+
+    let mut indices: Vec<CatIndexResponse> = read_json(response).await?;
+    // Hidden and system indices are rarely useful as context
+    indices.retain(|i| !i.index.starts_with('.'));
+    indices.sort_by(|a, b| a.index.cmp(&b.index));
+
+    let resources = indices
+        .into_iter()
+        .map(|i| {
+            let mut resource = RawResource::new(IndexResource::Mapping.uri(&i.index), format!("{} mapping", i.index));
+            // Closed indices have no document count
+            let details = match i.doc_count {
+                Some(count) => format!("{count} documents"),
+                None => "closed".to_string(),
+            };
+            resource.description = Some(format!("Field mappings of index {} ({details})", i.index));
+            resource.mime_type = Some("application/json".to_string());
+            resource.no_annotation()
+        })
+        .collect();
+
+    Ok(resources)
+}
+
+/// Read a resource for `resources/read`
+pub async fn read(es_client: &Elasticsearch, uri: &str) -> Result<ReadResourceResult, rmcp::Error> {
+    let Some((index, kind)) = parse_uri(uri) else {
+        return Err(rmcp::Error::resource_not_found(
+            format!("unknown resource '{uri}'"),
+            None,
+        ));
+    };
+
+    let value = match kind {
+        IndexResource::Mapping => {
+            let response = es_client
+                .indices()
+                .get_mapping(IndicesGetMappingParts::Index(&[index]))
+                .send()
+                .await;
+            read_json::<Value>(response).await?
+        }
+        IndexResource::Settings => {
+            let response = es_client
+                .indices()
+                .get_settings(IndicesGetSettingsParts::Index(&[index]))
+                .send()
+                .await;
+            read_json::<Value>(response).await?
+        }
+        IndexResource::Sample => {
+            let response = es_client
+                .search(SearchParts::Index(&[index]))
+                .body(json!({ "size": SAMPLE_SIZE }))
+                .send()
+                .await;
+            let response: SearchResult = read_json(response).await?;
+            let sources = response.hits.hits.into_iter().map(|hit| hit.source).collect::<Vec<_>>();
+            json!(sources)
+        }
+    };
+
+    let text = serde_json::to_string_pretty(&value).map_err(internal_error)?;
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some("application/json".to_string()),
+            text,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_uris() {
+        assert_eq!(
+            parse_uri("elasticsearch://index/logs-2025/mapping"),
+            Some(("logs-2025", IndexResource::Mapping))
+        );
+        assert_eq!(
+            parse_uri("elasticsearch://index/logs-*/sample"),
+            Some(("logs-*", IndexResource::Sample))
+        );
+        assert_eq!(parse_uri("elasticsearch://index//settings"), None);
+        assert_eq!(parse_uri("elasticsearch://index/logs/aliases"), None);
+        assert_eq!(parse_uri("file:///logs/mapping"), None);
+    }
+}
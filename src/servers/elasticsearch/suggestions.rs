@@ -0,0 +1,129 @@
+// Licensed to Elasticsearch B.V. under one or more contributor
+// license agreements. See the NOTICE file distributed with
+// this work for additional information regarding copyright
+// ownership. Elasticsearch B.V. licenses this file to you under
+// the Apache License, Version 2.0 (the "License"); you may
+// not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! "Did you mean" suggestions for index names that don't exist, which often happens when models
+//! guess index names.
+
+use crate::servers::elasticsearch::ArgumentCompletion;
+use crate::servers::elasticsearch::completion::Completions;
+use elasticsearch::Elasticsearch;
+
+/// Maximum number of suggestions
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Minimum similarity for a name to be suggested
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// Existing indices, aliases and data streams that are the closest to `name`, best match first.
+/// Names are shared with index name completion, so that repeated mistakes don't query the cluster
+/// each time. Failures are logged and result in no suggestions, as this is only used to improve
+/// error messages.
+pub async fn similar_indices(
+    completions: &Completions,
+    es_client: &Elasticsearch,
+    credentials: u64,
+    name: &str,
+) -> Vec<String> {
+    match completions
+        .names(es_client, credentials, &ArgumentCompletion::Index)
+        .await
+    {
+        Ok(candidates) => rank(name, &candidates),
+        Err(e) => {
+            tracing::debug!("Cannot get index names for suggestions: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Rank candidates by similarity to `name`. Hidden names are only considered if `name` is hidden.
+fn rank(name: &str, candidates: &[String]) -> Vec<String> {
+    let lower_name = name.to_lowercase();
+    let mut scored = candidates
+        .iter()
+        .filter(|c| *c != name && (name.starts_with('.') || !c.starts_with('.')))
+        .map(|c| (similarity(&lower_name, &c.to_lowercase()), c))
+        .filter(|(score, _)| *score >= MIN_SIMILARITY)
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|(s1, c1), (s2, c2)| s2.total_cmp(s1).then_with(|| c1.cmp(c2)));
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, c)| c.clone())
+        .collect()
+}
+
+/// Similarity between 0 and 1, based on the edit distance. Names that contain each other
+/// (e.g. `logs` and `logs-2025.06`) are considered similar.
+fn similarity(a: &str, b: &str) -> f64 {
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    let score = 1.0 - levenshtein(a, b) as f64 / max_len as f64;
+    if !a.is_empty() && !b.is_empty() && (a.contains(b) || b.contains(a)) {
+        score.max(0.8)
+    } else {
+        score
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_suggestions() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+
+        let candidates = [
+            "logs-app",
+            "log-app",
+            "metrics",
+            "logs-app-2025.06",
+            ".logs-app",
+            "traces",
+        ]
+        .map(String::from);
+        assert_eq!(
+            rank("logs-ap", &candidates),
+            ["logs-app", "logs-app-2025.06", "log-app"]
+        );
+        assert!(rank("something-else", &candidates).is_empty());
+    }
+}
@@ -15,11 +15,14 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use rmcp::handler::server::tool::ToolRouter;
 use serde::{Deserialize, Serialize};
 
+pub mod downstream;
 pub mod elasticsearch;
 
-/// Inclusion or exclusion list.
+/// Inclusion or exclusion list. Entries are either exact names or glob patterns where `*` matches
+/// any sequence of characters and `?` matches a single character (e.g. `get_*`).
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum IncludeExclude {
@@ -31,12 +34,90 @@ impl IncludeExclude {
     pub fn is_included(&self, name: &str) -> bool {
         use IncludeExclude::*;
         match self {
-            Include(includes) => includes.iter().map(|s| s.as_str()).any(|s| s == name),
-            Exclude(excludes) => excludes.iter().map(|s| s.as_str()).all(|s| s != name),
+            Include(includes) => includes.iter().any(|s| glob_match(s, name)),
+            Exclude(excludes) => !excludes.iter().any(|s| glob_match(s, name)),
         }
     }
 
     pub fn filter(&self, tools: &mut Vec<rmcp::model::Tool>) {
         tools.retain(|t| self.is_included(&t.name))
     }
+
+    /// Remove tools that are not included from a tool router, so that they're neither listed
+    /// nor callable.
+    pub fn filter_router<S: Send + Sync + 'static>(&self, router: &mut ToolRouter<S>) {
+        router.map.retain(|name, _| {
+            let included = self.is_included(name);
+            if !included {
+                tracing::info!("Tool '{name}' is excluded by the configuration");
+            }
+            included
+        })
+    }
+}
+
+/// Match a name against a glob pattern that supports `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last '*' in the pattern, and of the name character it was matched against
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last '*' absorb one more character
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("search", "search"));
+        assert!(!glob_match("search", "search_template"));
+        assert!(glob_match("get_*", "get_mappings"));
+        assert!(glob_match("get_*", "get_"));
+        assert!(!glob_match("get_*", "list_indices"));
+        assert!(glob_match("*_indices", "list_indices"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("es?l", "esql"));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(!glob_match("a*b*c", "aXXbYYb"));
+    }
+
+    #[test]
+    fn include_exclude() {
+        let excl = IncludeExclude::Exclude(vec!["search".to_string(), "get_*".to_string()]);
+        assert!(!excl.is_included("search"));
+        assert!(!excl.is_included("get_shards"));
+        assert!(excl.is_included("esql"));
+
+        let incl = IncludeExclude::Include(vec!["list_*".to_string(), "esql".to_string()]);
+        assert!(incl.is_included("list_indices"));
+        assert!(incl.is_included("esql"));
+        assert!(!incl.is_included("search"));
+    }
 }
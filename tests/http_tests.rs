@@ -29,6 +29,9 @@ use serde::de::DeserializeOwned;
 use serde_json::json;
 use sse_stream::SseStream;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Simple smoke test
 #[tokio::test]
@@ -163,6 +166,1118 @@ async fn end_to_end() -> anyhow::Result<()> {
     Ok(())
 }
 
+// Calls a custom ES|QL tool defined in a config file and checks parameter binding
+#[tokio::test]
+async fn custom_esql_tool() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_query",
+        axum::routing::post(async move |axum::Json(body): axum::Json<serde_json::Value>| {
+            match body["query"].as_str().unwrap() {
+                "row value = ?value | eval result = value + 42 | keep result" => {
+                    assert_eq!(body["params"], json!([{"value": 1}]));
+                }
+                // No empty list of parameters
+                _ => assert!(body.get("params").is_none()),
+            }
+            axum::Json(json!({
+                "columns": [{"name": "result", "type": "integer"}],
+                "values": [[43]]
+            }))
+        }),
+    );
+    let es_addr = start_es_mock(router).await?;
+
+    let config = write_config(
+        "custom_esql_tool",
+        json!({
+            "elasticsearch": {
+                "url": format!("http://{es_addr}/"),
+                "tools": {
+                    "custom": {
+                        "add-42": {
+                            "type": "esql",
+                            "description": "Adds 42 to the input value",
+                            "query": "row value = ?value | eval result = value + 42 | keep result",
+                            "format": "value",
+                            "parameters": {
+                                "value": { "title": "The value", "type": "number" }
+                            }
+                        },
+                        "answer": {
+                            "type": "esql",
+                            "description": "Returns 43",
+                            "query": "row result = 43",
+                            "format": "value",
+                            "parameters": {}
+                        }
+                    }
+                }
+            }
+        }),
+    )?;
+
+    let url = start_mcp_server(Some(config)).await?;
+
+    let tools: ListToolsResponse =
+        send_request(&url, json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"})).await?;
+    let tool = tools.result.tools.iter().find(|t| t.name == "add-42").unwrap();
+    assert_eq!(tool.input_schema.as_ref().unwrap()["required"], json!(["value"]));
+
+    let response: serde_json::Value = send_request(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": "add-42", "arguments": { "value": 1 } }
+        }),
+    )
+    .await?;
+
+    assert_eq!(response["result"]["content"][0]["text"], "43");
+
+    let result = call_tool(&url, "answer", json!({})).await?;
+    assert_eq!(result["content"][0]["text"], "43");
+
+    Ok(())
+}
+
+// Calls a custom inline search template tool and checks the template request
+#[tokio::test]
+async fn custom_search_template_tool() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/{index}/_search/template",
+        axum::routing::post(
+            async move |Path(index): Path<String>, axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(index, "my-index");
+                assert_eq!(body["source"]["query"]["term"]["some-field"], "{{param_1}}");
+                assert_eq!(body["params"], json!({"param_1": "foo"}));
+                axum::Json(json!({
+                    "hits": {
+                        "total": {"value": 1, "relation": "eq"},
+                        "hits": [{"_index": "my-index", "_id": "1", "_source": {"some-field": "foo"}}]
+                    }
+                }))
+            },
+        ),
+    );
+    let es_addr = start_es_mock(router).await?;
+
+    let config = write_config(
+        "custom_search_template_tool",
+        json!({
+            "elasticsearch": {
+                "url": format!("http://{es_addr}/"),
+                "tools": {
+                    "custom": {
+                        "an-inline-template": {
+                            "type": "search_template",
+                            "description": "An inline template",
+                            "index": "my-index",
+                            "template": {
+                                "query": { "term": { "some-field": "{{param_1}}" } }
+                            },
+                            "parameters": {
+                                "param_1": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+    )?;
+
+    let url = start_mcp_server(Some(config)).await?;
+
+    let response: serde_json::Value = send_request(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "an-inline-template", "arguments": { "param_1": "foo" } }
+        }),
+    )
+    .await?;
+
+    assert_eq!(response["result"]["content"][0]["text"], "Total results: 1, showing 1.");
+    assert_eq!(response["result"]["content"][1]["text"], "[{\"some-field\":\"foo\"}]");
+
+    Ok(())
+}
+
+// Excluded tools are neither listed nor callable
+#[tokio::test]
+async fn excluded_tools() -> anyhow::Result<()> {
+    let config = write_config(
+        "excluded_tools",
+        json!({
+            "elasticsearch": {
+                "url": "http://127.0.0.1:9200/",
+                "tools": {
+                    "exclude": ["search", "get_*"]
+                }
+            }
+        }),
+    )?;
+
+    let url = start_mcp_server(Some(config)).await?;
+
+    let tools: ListToolsResponse =
+        send_request(&url, json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"})).await?;
+    let names = tools.result.tools.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+    assert!(names.contains(&"list_indices"));
+    assert!(names.contains(&"esql"));
+    assert!(!names.contains(&"search"));
+    assert!(!names.contains(&"get_mappings"));
+    assert!(!names.contains(&"get_shards"));
+
+    let response: serde_json::Value = send_request(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": "search", "arguments": { "index": "foo", "query_body": {} } }
+        }),
+    )
+    .await?;
+    assert!(response["error"].is_object());
+
+    Ok(())
+}
+
+// Tools of a downstream stdio server (another instance of this server) are exposed with a prefix
+#[tokio::test]
+async fn downstream_server() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_cat/indices/{index}",
+        axum::routing::get(async move |Path(index): Path<String>| {
+            assert_eq!(index, "downstream-index");
+            axum::Json(json!([{"index": "downstream-index", "status": "open", "docs.count": "1"}]))
+        }),
+    );
+    let es_addr = start_es_mock(router).await?;
+
+    let config = write_config(
+        "downstream_server",
+        json!({
+            "elasticsearch": {
+                "url": "http://127.0.0.1:9200/",
+            },
+            "mcpServers": {
+                "other-es": {
+                    "type": "stdio",
+                    "command": env!("CARGO_BIN_EXE_elasticsearch-core-mcp-server"),
+                    "args": ["stdio"],
+                    "env": { "ES_URL": format!("http://{es_addr}/") },
+                    "prefix": "other_"
+                }
+            }
+        }),
+    )?;
+
+    let url = start_mcp_server(Some(config)).await?;
+
+    let tools: ListToolsResponse =
+        send_request(&url, json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"})).await?;
+    let names = tools.result.tools.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+    assert!(names.contains(&"list_indices"));
+    assert!(names.contains(&"other_list_indices"));
+
+    let response: serde_json::Value = send_request(
+        &url,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": "other_list_indices", "arguments": { "index_pattern": "downstream-index" } }
+        }),
+    )
+    .await?;
+    assert_eq!(response["result"]["content"][0]["text"], "Found 1 indices:");
+
+    Ok(())
+}
+
+// Index mappings are listed and read as resources, hidden indices excluded and closed ones included
+#[tokio::test]
+async fn index_resources() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/_cat/indices",
+            axum::routing::get(async || {
+                axum::Json(json!([
+                    {"index": "logs", "status": "open", "docs.count": "12"},
+                    {"index": ".internal", "status": "open", "docs.count": "1"},
+                    {"index": "archive", "status": "close", "docs.count": null}
+                ]))
+            }),
+        )
+        .route(
+            "/logs/_mapping",
+            axum::routing::get(async || {
+                axum::Json(json!({"logs": {"mappings": {"properties": {"message": {"type": "text"}}}}}))
+            }),
+        );
+    let url = start_servers("index_resources", router).await?;
+
+    let result = rpc(&url, "resources/list", json!({})).await?;
+    assert_eq!(
+        result["resources"],
+        json!([
+            {
+                "uri": "elasticsearch://index/archive/mapping",
+                "name": "archive mapping",
+                "description": "Field mappings of index archive (closed)",
+                "mimeType": "application/json"
+            },
+            {
+                "uri": "elasticsearch://index/logs/mapping",
+                "name": "logs mapping",
+                "description": "Field mappings of index logs (12 documents)",
+                "mimeType": "application/json"
+            }
+        ])
+    );
+
+    let result = rpc(&url, "resources/templates/list", json!({})).await?;
+    assert_eq!(
+        result["resourceTemplates"][2]["uriTemplate"],
+        "elasticsearch://index/{name}/sample"
+    );
+
+    let result = rpc(
+        &url,
+        "resources/read",
+        json!({ "uri": "elasticsearch://index/logs/mapping" }),
+    )
+    .await?;
+    let text = result["contents"][0]["text"].as_str().unwrap();
+    let mapping: serde_json::Value = serde_json::from_str(text)?;
+    assert_eq!(mapping["logs"]["mappings"]["properties"]["message"]["type"], "text");
+
+    Ok(())
+}
+
+// Index names of a resource template argument are completed, best matches first
+#[tokio::test]
+async fn index_name_completion() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_resolve/index/{name}",
+        axum::routing::get(async |axum::extract::RawQuery(query): axum::extract::RawQuery| {
+            // Hidden names are included, and only completed if asked for
+            assert_eq!(query.as_deref(), Some("expand_wildcards=all"));
+            axum::Json(json!({
+                "indices": [{"name": "logs-1"}, {"name": ".security"}, {"name": "metrics"}],
+                "aliases": [{"name": "logs"}],
+                "data_streams": [{"name": "app-logs"}]
+            }))
+        }),
+    );
+    let url = start_servers("index_name_completion", router).await?;
+
+    let result = rpc(
+        &url,
+        "completion/complete",
+        json!({
+            "ref": { "type": "ref/resource", "uri": "elasticsearch://index/{name}/mapping" },
+            "argument": { "name": "name", "value": "log" }
+        }),
+    )
+    .await?;
+    assert_eq!(result["completion"]["values"], json!(["logs", "logs-1", "app-logs"]));
+
+    Ok(())
+}
+
+// Elasticsearch errors are returned as tool error results with their details
+#[tokio::test]
+async fn elasticsearch_error_result() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_query",
+        axum::routing::post(async || {
+            (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(json!({
+                    "error": {
+                        "root_cause": [{
+                            "type": "verification_exception",
+                            "reason": "Found 1 problem\nline 1:21: Unknown column [foo]"
+                        }],
+                        "type": "verification_exception",
+                        "reason": "Found 1 problem\nline 1:21: Unknown column [foo]"
+                    },
+                    "status": 400
+                })),
+            )
+        }),
+    );
+    let url = start_servers("elasticsearch_error_result", router).await?;
+
+    let result = call_tool(&url, "esql", json!({ "query": "from logs | keep foo" })).await?;
+
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.starts_with("Elasticsearch error (400, verification_exception): Found 1 problem"));
+    assert!(text.contains("Unknown column [foo]"));
+
+    Ok(())
+}
+
+// A missing index comes with suggestions of similar existing names, for search, ES|QL and mappings
+#[tokio::test]
+async fn index_not_found_suggestions() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/{index}/_search",
+            axum::routing::post(async || {
+                (
+                    axum::http::StatusCode::NOT_FOUND,
+                    axum::Json(json!({
+                        "error": {
+                            "type": "index_not_found_exception",
+                            "reason": "no such index [logz]",
+                            "index": "logz"
+                        },
+                        "status": 404
+                    })),
+                )
+            }),
+        )
+        .route(
+            "/_query",
+            axum::routing::post(async || {
+                (
+                    axum::http::StatusCode::BAD_REQUEST,
+                    axum::Json(json!({
+                        "error": {
+                            "type": "verification_exception",
+                            "reason": "Found 1 problem\nline 1:6: Unknown index [logz]"
+                        },
+                        "status": 400
+                    })),
+                )
+            }),
+        )
+        .route("/{index}/_mapping", axum::routing::get(async || axum::Json(json!({}))))
+        .route(
+            "/_resolve/index/{name}",
+            axum::routing::get(async || {
+                axum::Json(json!({
+                    "indices": [{"name": "logs"}, {"name": "metrics"}],
+                    "aliases": [{"name": "logz-old"}]
+                }))
+            }),
+        );
+    let url = start_servers("index_not_found_suggestions", router).await?;
+
+    let result = call_tool(&url, "search", json!({ "index": "logz", "query_body": {} })).await?;
+
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("Did you mean: logz-old, logs?"), "{text}");
+
+    let result = call_tool(&url, "esql", json!({ "query": "from logz | limit 10" })).await?;
+
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("Did you mean: logz-old, logs?"), "{text}");
+
+    let result = call_tool(&url, "get_mappings", json!({ "index": "logz*" })).await?;
+
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(
+        text.starts_with("No index matches 'logz*'. Did you mean: logz-old"),
+        "{text}"
+    );
+
+    Ok(())
+}
+
+// A paginated search returns a cursor, and the PIT is closed after the last page
+#[tokio::test]
+async fn search_pagination() -> anyhow::Result<()> {
+    let closed = Arc::new(AtomicBool::new(false));
+    let closed_clone = closed.clone();
+    let router = Router::new()
+        .route(
+            "/{index}/_pit",
+            axum::routing::post(async || axum::Json(json!({"id": "pit-1"}))),
+        )
+        .route(
+            "/_search",
+            axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(body["pit"]["id"], "pit-1");
+                assert_eq!(body["query"], json!({"match_all": {}}));
+                let hits = match body.get("search_after") {
+                    None => json!([{"_source": {"id": 1}, "sort": [1]}]),
+                    Some(search_after) => {
+                        assert_eq!(search_after, &json!([1]));
+                        json!([])
+                    }
+                };
+                axum::Json(json!({"pit_id": "pit-1", "hits": {"total": {"value": 1}, "hits": hits}}))
+            }),
+        )
+        .route(
+            "/_pit",
+            axum::routing::delete(async move || {
+                closed_clone.store(true, Ordering::SeqCst);
+                axum::Json(json!({"succeeded": true}))
+            }),
+        );
+    let url = start_servers("search_pagination", router).await?;
+
+    let result = call_tool(
+        &url,
+        "search",
+        json!({
+            "index": "logs",
+            "query_body": { "query": { "match_all": {} }, "size": 1 },
+            "paginate": true
+        }),
+    )
+    .await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[1]["text"], "[{\"id\":1}]");
+    let text = content[2]["text"].as_str().unwrap();
+    let cursor = text.rsplit_once("cursor: ").unwrap().1;
+
+    let result = call_tool(&url, "search", json!({ "cursor": cursor })).await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content.last().unwrap()["text"], "No more results.");
+    assert!(closed.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+// The PIT of a paginated search is closed if the first page fails
+#[tokio::test]
+async fn search_pagination_error() -> anyhow::Result<()> {
+    let closed = Arc::new(AtomicBool::new(false));
+    let closed_clone = closed.clone();
+    let router = Router::new()
+        .route(
+            "/{index}/_pit",
+            axum::routing::post(async || axum::Json(json!({"id": "pit-1"}))),
+        )
+        .route(
+            "/_search",
+            axum::routing::post(async || {
+                let error = json!({
+                    "error": {"type": "parsing_exception", "reason": "unknown query [matchall]"},
+                    "status": 400
+                });
+                (axum::http::StatusCode::BAD_REQUEST, axum::Json(error))
+            }),
+        )
+        .route(
+            "/_pit",
+            axum::routing::delete(async move |axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(body["id"], "pit-1");
+                closed_clone.store(true, Ordering::SeqCst);
+                axum::Json(json!({"succeeded": true}))
+            }),
+        );
+    let url = start_servers("search_pagination_error", router).await?;
+
+    let result = call_tool(
+        &url,
+        "search",
+        json!({
+            "index": "logs",
+            "query_body": { "query": { "matchall": {} } },
+            "paginate": true
+        }),
+    )
+    .await?;
+
+    assert_eq!(result["isError"], true);
+    assert!(closed.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+// ES|QL named parameters and filter are sent to Elasticsearch, partial results are flagged
+#[tokio::test]
+async fn esql_params_and_filter() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_query",
+        axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+            assert_eq!(body["query"], "from logs | where host == ?host | stats count()");
+            assert_eq!(body["params"], json!([{"host": "web-1"}]));
+            assert_eq!(body["filter"], json!({"range": {"@timestamp": {"gte": "now-1h"}}}));
+            axum::Json(json!({
+                "is_partial": true,
+                "columns": [{"name": "count()", "type": "long"}],
+                "values": [[12]]
+            }))
+        }),
+    );
+    let url = start_servers("esql_params_and_filter", router).await?;
+
+    let result = call_tool(
+        &url,
+        "esql",
+        json!({
+            "query": "from logs | where host == ?host | stats count()",
+            "params": { "host": "web-1" },
+            "filter": { "range": { "@timestamp": { "gte": "now-1h" } } }
+        }),
+    )
+    .await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert!(
+        content[1]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Warning: results are partial")
+    );
+    assert_eq!(content[2]["text"], "[{\"count()\":12}]");
+
+    Ok(())
+}
+
+// An async ES|QL query that is still running returns its id, to get the results later
+#[tokio::test]
+async fn esql_async_query() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/_query/async",
+            axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(body["query"], "from logs | stats count()");
+                assert!(body["keep_alive"].is_string());
+                axum::Json(json!({ "id": "q1", "is_running": true }))
+            }),
+        )
+        .route(
+            "/_query/async/q1",
+            axum::routing::get(async || {
+                axum::Json(json!({
+                    "id": "q1",
+                    "is_running": false,
+                    "columns": [{"name": "count()", "type": "long"}],
+                    "values": [[42]]
+                }))
+            }),
+        );
+    let url = start_servers("esql_async_query", router).await?;
+
+    // No wait: the query id is returned
+    let result = call_tool(
+        &url,
+        "esql_async_submit",
+        json!({
+            "query": "from logs | stats count()",
+            "wait_seconds": 0
+        }),
+    )
+    .await?;
+
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.starts_with("The query is still running"));
+    assert!(text.contains("id 'q1'"));
+
+    let result = call_tool(&url, "get_async_result", json!({ "id": "q1", "type": "esql" })).await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[1]["text"], "[{\"count()\":42}]");
+    assert!(content[2]["text"].as_str().unwrap().contains("delete_async_result"));
+
+    Ok(())
+}
+
+// A failed async search has an error and no response, and is returned as a tool error
+#[tokio::test]
+async fn async_search_failure() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/logs/_async_search",
+        axum::routing::post(async || {
+            axum::Json(json!({
+                "id": "s1",
+                "is_running": false,
+                "is_partial": true,
+                "completion_status": 400,
+                "error": {
+                    "type": "search_phase_execution_exception",
+                    "reason": "all shards failed",
+                    "root_cause": [{ "type": "query_shard_exception", "reason": "failed to create query" }]
+                }
+            }))
+        }),
+    );
+    let url = start_servers("async_search_failure", router).await?;
+
+    let result = call_tool(
+        &url,
+        "async_search_submit",
+        json!({ "index": "logs", "query_body": { "query": { "match_all": {} } } }),
+    )
+    .await?;
+
+    assert_eq!(result["isError"], true);
+    let text = result["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("failed to create query"), "{text}");
+
+    Ok(())
+}
+
+// Cluster health combines health, nodes and pending tasks, with unhealthy indices
+#[tokio::test]
+async fn cluster_health() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/_cluster/health",
+            axum::routing::get(async || {
+                axum::Json(json!({
+                    "cluster_name": "prod",
+                    "status": "yellow",
+                    "number_of_nodes": 2,
+                    "number_of_data_nodes": 2,
+                    "active_primary_shards": 4,
+                    "active_shards": 6,
+                    "relocating_shards": 0,
+                    "initializing_shards": 0,
+                    "unassigned_shards": 2,
+                    "delayed_unassigned_shards": 0,
+                    "indices": {
+                        "logs": {"status": "green", "number_of_shards": 2, "number_of_replicas": 1,
+                            "active_primary_shards": 2, "active_shards": 4, "relocating_shards": 0,
+                            "initializing_shards": 0, "unassigned_shards": 0},
+                        "metrics": {"status": "yellow", "number_of_shards": 2, "number_of_replicas": 1,
+                            "active_primary_shards": 2, "active_shards": 2, "relocating_shards": 0,
+                            "initializing_shards": 0, "unassigned_shards": 2}
+                    }
+                }))
+            }),
+        )
+        .route(
+            "/_cat/nodes",
+            axum::routing::get(async || {
+                axum::Json(json!([
+                    {"name": "node-1", "node.role": "dm", "master": "*", "heap.percent": "45", "ram.percent": "90",
+                        "cpu": "12", "load_1m": "0.50", "disk.used_percent": "91.20", "disk.avail": "8.8gb"},
+                    {"name": "node-2", "node.role": "dm", "master": "-", "heap.percent": "40", "ram.percent": "88",
+                        "cpu": "10", "load_1m": "0.40", "disk.used_percent": "50.00", "disk.avail": "50gb"}
+                ]))
+            }),
+        )
+        .route(
+            "/_cluster/pending_tasks",
+            axum::routing::get(async || axum::Json(json!({ "tasks": [] }))),
+        );
+    let url = start_servers("cluster_health", router).await?;
+
+    let result = call_tool(&url, "cluster_health", json!({ "index_details": true })).await?;
+
+    let content = result["content"].as_array().unwrap();
+    let summary = content[0]["text"].as_str().unwrap();
+    assert!(summary.starts_with("Cluster prod is yellow. 2 nodes"));
+    assert!(summary.contains("2 unassigned"));
+
+    let nodes: serde_json::Value = serde_json::from_str(content[2]["text"].as_str().unwrap())?;
+    assert_eq!(nodes[0]["disk.used_percent"], 91.2);
+
+    // Only indices that are not green
+    assert_eq!(content[3]["text"], "Health of 1 indices:");
+    let indices: serde_json::Value = serde_json::from_str(content[4]["text"].as_str().unwrap())?;
+    assert_eq!(indices["metrics"]["unassigned_shards"], 2);
+
+    Ok(())
+}
+
+// The allocation explanation of an unassigned shard is summarized as text
+#[tokio::test]
+async fn explain_allocation() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/_cluster/allocation/explain",
+        axum::routing::get(async || {
+            axum::Json(json!({
+                "index": "metrics",
+                "shard": 0,
+                "primary": false,
+                "current_state": "unassigned",
+                "unassigned_info": {"reason": "NODE_LEFT", "at": "2025-06-01T10:00:00.000Z"},
+                "can_allocate": "no",
+                "allocate_explanation": "Elasticsearch isn't allowed to allocate this shard to any of the nodes in the cluster.",
+                "node_allocation_decisions": [{
+                    "node_id": "abc",
+                    "node_name": "node-1",
+                    "node_decision": "no",
+                    "deciders": [{
+                        "decider": "same_shard",
+                        "decision": "NO",
+                        "explanation": "a copy of this shard is already allocated to this node"
+                    }]
+                }]
+            }))
+        }),
+    );
+    let url = start_servers("explain_allocation", router).await?;
+
+    let result = call_tool(&url, "explain_allocation", json!({})).await?;
+
+    let text = result["content"][0]["text"].as_str().unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Shard 0 of index metrics (replica) is unassigned.");
+    assert!(lines[1].starts_with("It was unassigned because of NODE_LEFT"));
+    assert!(lines[2].starts_with("Can be allocated: no."));
+    assert_eq!(
+        lines[4],
+        "- node-1: no. same_shard says NO: a copy of this shard is already allocated to this node"
+    );
+
+    Ok(())
+}
+
+// Index statistics are sorted and truncated, with human readable sizes
+#[tokio::test]
+async fn index_stats() -> anyhow::Result<()> {
+    fn stats(docs: u64, bytes: u64) -> serde_json::Value {
+        json!({
+            "docs": {"count": docs, "deleted": 1},
+            "store": {"size_in_bytes": bytes},
+            "segments": {"count": 3},
+            "search": {"query_total": 10}
+        })
+    }
+    let router = Router::new()
+        .route(
+            "/logs-*/_stats/docs,store,segments,refresh,search,indexing",
+            axum::routing::get(async || {
+                axum::Json(json!({
+                    "_all": {"primaries": stats(3000, 3072), "total": stats(3000, 6144)},
+                    "indices": {
+                        "logs-1": {"primaries": stats(1000, 1024), "total": stats(1000, 2048)},
+                        "logs-2": {"primaries": stats(2000, 2048), "total": stats(2000, 4096)}
+                    }
+                }))
+            }),
+        )
+        .route(
+            "/_cat/indices/logs-*",
+            axum::routing::get(async || {
+                axum::Json(json!([
+                    {"index": "logs-1", "health": "green", "creation.date": "1700000000000",
+                        "creation.date.string": "2023-11-14T22:13:20.000Z"},
+                    {"index": "logs-2", "health": "yellow", "creation.date": "1700000000000",
+                        "creation.date.string": "2023-11-14T22:13:20.000Z"}
+                ]))
+            }),
+        );
+    let url = start_servers("index_stats", router).await?;
+
+    let result = call_tool(
+        &url,
+        "index_stats",
+        json!({ "index": "logs-*", "sort_by": "docs", "top": 1 }),
+    )
+    .await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(
+        content[0]["text"],
+        "2 indices matching logs-*: 3000 documents, 6.0kb in total, 3.0kb for primaries. Showing the top 1."
+    );
+    let indices: serde_json::Value = serde_json::from_str(content[1]["text"].as_str().unwrap())?;
+    assert_eq!(indices[0]["index"], "logs-2");
+    assert_eq!(indices[0]["health"], "yellow");
+    assert_eq!(indices[0]["store_size"], "4.0kb");
+    assert_eq!(indices[0]["segments"], 3);
+    assert!(indices[0]["growth_per_day"].is_string());
+
+    Ok(())
+}
+
+// A text field is described using its keyword multi-field
+#[tokio::test]
+async fn describe_field() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/logs/_mapping",
+            axum::routing::get(async || {
+                axum::Json(json!({ "logs": { "mappings": { "properties": {
+                    "message": { "type": "text", "fields": { "keyword": { "type": "keyword" } } }
+                }}}}))
+            }),
+        )
+        .route(
+            "/logs/_search",
+            axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(body["size"], 0);
+                assert_eq!(body["aggs"]["top"]["terms"]["field"], "message.keyword");
+                assert_eq!(body["aggs"]["cardinality"]["cardinality"]["field"], "message.keyword");
+                axum::Json(json!({
+                    "hits": { "total": { "value": 100 }, "hits": [] },
+                    "aggregations": {
+                        "missing": { "doc_count": 25 },
+                        "cardinality": { "value": 2 },
+                        "top": {
+                            "sum_other_doc_count": 0,
+                            "buckets": [{ "key": "started", "doc_count": 50 }, { "key": "stopped", "doc_count": 25 }]
+                        }
+                    }
+                }))
+            }),
+        );
+    let url = start_servers("describe_field", router).await?;
+
+    let result = call_tool(&url, "describe_field", json!({ "index": "logs", "field": "message" })).await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[0]["text"], "Field message of type text in logs:");
+    let description: serde_json::Value = serde_json::from_str(content[1]["text"].as_str().unwrap())?;
+    assert_eq!(
+        description,
+        json!({
+            "type": "text",
+            "aggregated_field": "message.keyword",
+            "total_docs": 100,
+            "missing_docs": 25,
+            "missing_ratio": 0.25,
+            "cardinality": 2,
+            "top_values": [{ "value": "started", "count": 50 }, { "value": "stopped", "count": 25 }]
+        })
+    );
+
+    Ok(())
+}
+
+// A field with a different type in some indices isn't described, and its types are reported
+#[tokio::test]
+async fn describe_field_type_conflict() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/logs-*/_mapping",
+        axum::routing::get(async || {
+            axum::Json(json!({
+                "logs-1": { "mappings": { "properties": { "status": { "type": "keyword" } } } },
+                "logs-2": { "mappings": { "properties": { "status": { "type": "long" } } } }
+            }))
+        }),
+    );
+    let url = start_servers("describe_field_type_conflict", router).await?;
+
+    let result = call_tool(&url, "describe_field", json!({ "index": "logs-*", "field": "status" })).await?;
+
+    assert_eq!(result["isError"], true);
+    assert_eq!(
+        result["content"][0]["text"],
+        "Field 'status' has different types in indices matching 'logs-*': keyword in logs-1; long in logs-2. \
+         Describe it in indices that have the same type."
+    );
+
+    Ok(())
+}
+
+// Count and query validation only send the query of the query body
+#[tokio::test]
+async fn count_and_validate_query() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/logs/_count",
+            axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+                // Only the query is sent
+                assert_eq!(body, json!({ "query": { "match": { "message": "error" } } }));
+                axum::Json(json!({ "count": 42 }))
+            }),
+        )
+        .route(
+            "/logs/_validate/query",
+            axum::routing::post(async || {
+                axum::Json(json!({
+                    "valid": true,
+                    "explanations": [
+                        { "index": "logs", "valid": true, "explanation": "message:error" }
+                    ]
+                }))
+            }),
+        );
+    let url = start_servers("count_and_validate_query", router).await?;
+
+    let arguments = json!({
+        "index": "logs",
+        "query_body": { "query": { "match": { "message": "error" } }, "size": 10 }
+    });
+
+    let result = call_tool(&url, "count", arguments.clone()).await?;
+    assert_eq!(result["content"][0]["text"], "42 documents match the query in logs.");
+
+    let result = call_tool(&url, "validate_query", arguments).await?;
+    assert_eq!(
+        result["content"][0]["text"],
+        "The query is valid.\nLucene query: message:error"
+    );
+
+    Ok(())
+}
+
+// Documents are sampled with a seeded random score, and long values are truncated
+#[tokio::test]
+async fn sample_documents() -> anyhow::Result<()> {
+    let router = Router::new().route(
+        "/logs/_search",
+        axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+            assert_eq!(body["size"], 2);
+            assert_eq!(
+                body["query"]["function_score"]["random_score"],
+                json!({ "seed": 42, "field": "_seq_no" })
+            );
+            axum::Json(json!({
+                "hits": {
+                    "total": { "value": 1000 },
+                    "hits": [
+                        { "_index": "logs", "_id": "1", "_source": { "message": "a".repeat(300) } },
+                        { "_index": "logs", "_id": "2", "_source": { "tags": [1, 2, 3, 4, 5, 6] } }
+                    ]
+                }
+            }))
+        }),
+    );
+    let url = start_servers("sample_documents", router).await?;
+
+    let result = call_tool(
+        &url,
+        "sample_documents",
+        json!({ "index": "logs", "size": 2, "seed": 42 }),
+    )
+    .await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[0]["text"], "Total results: 1000, showing 2.");
+    let docs: serde_json::Value = serde_json::from_str(content[1]["text"].as_str().unwrap())?;
+    assert!(
+        docs[0]["message"]
+            .as_str()
+            .unwrap()
+            .ends_with("... (100 more characters)")
+    );
+    assert_eq!(docs[1]["tags"], json!([1, 2, 3, 4, 5, "... (1 more items)"]));
+
+    Ok(())
+}
+
+// Documents are fetched by id, and missing documents are reported
+#[tokio::test]
+async fn get_documents() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/logs/_doc/{id}",
+            axum::routing::get(async |axum::extract::Path(id): axum::extract::Path<String>| {
+                if id == "1" {
+                    (
+                        axum::http::StatusCode::OK,
+                        axum::Json(
+                            json!({ "_index": "logs", "_id": "1", "found": true, "_source": { "message": "hello" } }),
+                        ),
+                    )
+                } else {
+                    (
+                        axum::http::StatusCode::NOT_FOUND,
+                        axum::Json(json!({ "_index": "logs", "_id": id, "found": false })),
+                    )
+                }
+            }),
+        )
+        .route(
+            "/logs/_mget",
+            axum::routing::post(async |axum::Json(body): axum::Json<serde_json::Value>| {
+                assert_eq!(body, json!({ "ids": ["1", "2"] }));
+                axum::Json(json!({ "docs": [
+                    { "_index": "logs", "_id": "1", "found": true, "_source": { "message": "hello" } },
+                    { "_index": "logs", "_id": "2", "found": false }
+                ]}))
+            }),
+        );
+    let url = start_servers("get_documents", router).await?;
+
+    let result = call_tool(&url, "get_document", json!({ "index": "logs", "id": "1" })).await?;
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[0]["text"], "Document 1 in index logs:");
+    assert_eq!(content[1]["text"], "{\"message\":\"hello\"}");
+
+    let result = call_tool(&url, "get_document", json!({ "index": "logs", "id": "2" })).await?;
+    assert_eq!(result["content"][0]["text"], "Document 2 not found in logs.");
+
+    let result = call_tool(&url, "multi_get", json!({ "index": "logs", "ids": ["1", "2"] })).await?;
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content[0]["text"], "Found 1 of 2 documents.");
+    assert_eq!(content[2]["text"], "Not found: 2");
+
+    Ok(())
+}
+
+// Data streams are listed with their backing indices
+#[tokio::test]
+async fn data_streams() -> anyhow::Result<()> {
+    let router = Router::new()
+        .route(
+            "/_cat/indices/{pattern}",
+            axum::routing::get(async || {
+                axum::Json(json!([
+                    { "index": ".ds-logs-app-2025.06.01-000001", "status": "open", "docs.count": "100" },
+                    { "index": ".ds-logs-app-2025.06.02-000002", "status": "open", "docs.count": "50" },
+                    { "index": "products", "status": "open", "docs.count": "10" }
+                ]))
+            }),
+        )
+        .route(
+            "/_data_stream",
+            axum::routing::get(async || {
+                axum::Json(json!({ "data_streams": [{
+                    "name": "logs-app",
+                    "generation": 2,
+                    "status": "GREEN",
+                    "template": "logs",
+                    "ilm_policy": "logs",
+                    "timestamp_field": { "name": "@timestamp" },
+                    "indices": [
+                        { "index_name": ".ds-logs-app-2025.06.01-000001", "index_uuid": "a" },
+                        { "index_name": ".ds-logs-app-2025.06.02-000002", "index_uuid": "b" }
+                    ]
+                }]}))
+            }),
+        );
+    let url = start_servers("data_streams", router).await?;
+
+    let result = call_tool(
+        &url,
+        "list_indices",
+        json!({ "index_pattern": "*", "group_data_streams": true }),
+    )
+    .await?;
+
+    let content = result["content"].as_array().unwrap();
+    assert!(
+        content[0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Found 1 indices and 1 data streams.")
+    );
+    let streams: serde_json::Value = serde_json::from_str(content[3]["text"].as_str().unwrap())?;
+    assert_eq!(
+        streams,
+        json!([{
+            "data_stream": "logs-app",
+            "docs.count": 150,
+            "backing_indices": [".ds-logs-app-2025.06.01-000001", ".ds-logs-app-2025.06.02-000002"]
+        }])
+    );
+
+    let result = call_tool(&url, "list_data_streams", json!({})).await?;
+
+    let streams: serde_json::Value = serde_json::from_str(result["content"][1]["text"].as_str().unwrap())?;
+    assert_eq!(streams[0]["generation"], 2);
+    assert_eq!(streams[0]["backing_indices"], 2);
+    assert_eq!(streams[0]["write_index"], ".ds-logs-app-2025.06.02-000002");
+
+    Ok(())
+}
+
 const LOCALHOST_0: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0);
 
 fn find_address() -> anyhow::Result<SocketAddr> {
@@ -170,6 +1285,82 @@ fn find_address() -> anyhow::Result<SocketAddr> {
     Ok(TcpListener::bind(LOCALHOST_0)?.local_addr()?)
 }
 
+/// Start an ES mock server and return its address
+async fn start_es_mock(router: Router) -> anyhow::Result<SocketAddr> {
+    let listener = tokio::net::TcpListener::bind(LOCALHOST_0).await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async { axum::serve(listener, router).await });
+    Ok(addr)
+}
+
+/// Write a configuration file in the temp directory
+fn write_config(name: &str, config: serde_json::Value) -> anyhow::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("elastic-mcp-{name}-{}.json5", std::process::id()));
+    std::fs::write(&path, serde_json::to_string_pretty(&config)?)?;
+    Ok(path)
+}
+
+/// Start an http MCP server and return its url
+async fn start_mcp_server(config: Option<PathBuf>) -> anyhow::Result<String> {
+    let addr = find_address()?;
+    let cli = cli::Cli {
+        container_mode: false,
+        command: cli::Command::Http(cli::HttpCommand {
+            config,
+            address: Some(addr),
+            sse: false,
+        }),
+    };
+
+    tokio::spawn(async move { cli.run().await });
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+    Ok(format!("http://127.0.0.1:{}/mcp", addr.port()))
+}
+
+/// Start an ES mock server with `router` and an http MCP server that uses it, and return the MCP
+/// server url
+async fn start_servers(name: &str, router: Router) -> anyhow::Result<String> {
+    let es_addr = start_es_mock(router).await?;
+    let config = write_config(
+        name,
+        json!({ "elasticsearch": { "url": format!("http://{es_addr}/") } }),
+    )?;
+    start_mcp_server(Some(config)).await
+}
+
+/// Send a JSON-RPC request and return its result
+async fn rpc(url: &str, method: &str, params: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+    let mut response: serde_json::Value = send_request(
+        url,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+    )
+    .await?;
+    if response["error"].is_object() {
+        bail!("{method} failed: {}", response["error"]);
+    }
+    Ok(response["result"].take())
+}
+
+/// Call a tool and return its result
+async fn call_tool(url: &str, name: &str, arguments: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+    rpc(url, "tools/call", json!({ "name": name, "arguments": arguments })).await
+}
+
+async fn send_request<T: DeserializeOwned>(url: &str, body: serde_json::Value) -> anyhow::Result<T> {
+    let response = Client::builder()
+        .build()?
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT, "application/json, text/event-stream")
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    parse_response(response).await
+}
+
 async fn parse_response<T: DeserializeOwned>(response: reqwest::Response) -> anyhow::Result<T> {
     let result = match response.headers().get(CONTENT_TYPE) {
         Some(v) if v == "application/json" => response.json().await?,
@@ -207,6 +1398,7 @@ struct ToolResult {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 struct Tool {
     name: String,